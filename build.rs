@@ -0,0 +1,15 @@
+use std::env;
+use std::path::PathBuf;
+
+// generate the OLM registry grpc server and client from proto/registry.proto
+// protoc is vendored so that no system install is needed
+fn main() -> Result<(), Box<dyn std::error::Error>> {
+    env::set_var("PROTOC", protoc_bin_vendored::protoc_bin_path()?);
+    let out_dir = PathBuf::from(env::var("OUT_DIR")?);
+    tonic_build::configure()
+        // descriptor set for grpc server reflection (grpcurl)
+        .file_descriptor_set_path(out_dir.join("registry_descriptor.bin"))
+        .compile(&["proto/registry.proto"], &["proto"])?;
+    println!("cargo:rerun-if-changed=proto/registry.proto");
+    Ok(())
+}
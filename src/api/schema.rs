@@ -1,3 +1,7 @@
+use crate::query::diff::DiffFormat;
+use crate::query::graph::GraphFormat;
+use crate::query::imageset::ConfigKind;
+use crate::query::output::OutputFormat;
 use clap::{Parser, Subcommand};
 
 /// rust-container-tool cli struct
@@ -14,6 +18,10 @@ pub struct Cli {
     /// set the loglevel. Valid arguments are info, debug, trace
     #[arg(value_enum, long, value_name = "loglevel", default_value = "info")]
     pub loglevel: Option<String>,
+
+    /// location of the catalog state file (overrides $OPERATOR_CATALOG_VIEWER_STATE, default $XDG_DATA_HOME/operator-catalog-viewer/state.json)
+    #[arg(long, global = true, value_name = "state-file")]
+    pub state_file: Option<String>,
 }
 
 #[derive(Subcommand, Debug)]
@@ -35,6 +43,44 @@ pub enum Commands {
         // process all architectures
         #[arg(short, long, value_name = "all-arch", default_value = "false")]
         all_arch: Option<bool>,
+
+        /// number of snapshots (previous contents of a catalog tag) to keep per architecture, 0 disables the history
+        #[arg(long, value_name = "keep-snapshots", default_value = "3")]
+        keep_snapshots: usize,
+
+        /// remove snapshots older than the given number of days
+        #[arg(long, value_name = "max-snapshot-age")]
+        max_snapshot_age: Option<i64>,
+    },
+    /// Import subcommand (register a catalog from an OCI layout or docker/oci archive, for disconnected hosts)
+    Import {
+        #[arg(
+            short,
+            long,
+            value_name = "working-dir",
+            help = "Sets the directory used to share existing caches with other catalog tooling (required)"
+        )]
+        working_dir: String,
+
+        /// OCI layout directory or tar archive ('docker save', 'skopeo copy ... docker-archive:/oci-archive:')
+        #[arg(value_name = "path")]
+        path: String,
+
+        /// image reference to register the catalog as i.e registry.redhat.io/redhat/redhat-operator-index:v4.18 (defaults to the reference recorded in the archive)
+        #[arg(long, value_name = "image")]
+        image: Option<String>,
+
+        // process all architectures
+        #[arg(short, long, value_name = "all-arch", default_value = "false")]
+        all_arch: Option<bool>,
+
+        /// number of snapshots (previous contents of a catalog tag) to keep per architecture, 0 disables the history
+        #[arg(long, value_name = "keep-snapshots", default_value = "3")]
+        keep_snapshots: usize,
+
+        /// remove snapshots older than the given number of days
+        #[arg(long, value_name = "max-snapshot-age")]
+        max_snapshot_age: Option<i64>,
     },
     /// View subcommand (launches the TUI application)
     View {
@@ -51,5 +97,300 @@ pub enum Commands {
         // used with dev_enable to test
         #[arg(short, long, value_name = "operator", default_value = "")]
         operator: Option<String>,
+
+        /// architecture of the catalog to view (only catalogs updated with --all-arch have more than amd64)
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// catalog to view i.e redhat-operator-index:v4.18 (prefix and fuzzy matching supported), skips the selection prompt
+        #[arg(long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// view a snapshot of the catalog (position or id/digest prefix as listed by 'snapshots list')
+        #[arg(long, value_name = "snapshot")]
+        snapshot: Option<String>,
+
+        /// directory where the graphs exported with 'e' are written (defaults to exports/ next to the state file)
+        #[arg(long, value_name = "export-dir")]
+        export_dir: Option<String>,
+    },
+    /// Query subcommand (scriptable access to the downloaded catalogs)
+    Query {
+        #[command(subcommand)]
+        query: QueryCommands,
+    },
+    /// Show subcommand (full details of a catalog object)
+    Show {
+        #[command(subcommand)]
+        show: ShowCommands,
+    },
+    /// Snapshots subcommand (previous contents of a catalog tag)
+    Snapshots {
+        #[command(subcommand)]
+        snapshots: SnapshotCommands,
+    },
+    /// Generate subcommand (write a mirror config for the selected packages of a catalog)
+    Generate {
+        /// catalog i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// package to mirror as package[:channel][@min..max] (repeat for more packages/channels, omit for the full catalog)
+        #[arg(short, long, value_name = "package")]
+        package: Vec<String>,
+
+        /// kind of config to generate
+        #[arg(short, long, value_enum, default_value = "imageset")]
+        kind: ConfigKind,
+
+        /// write the config to a file instead of stdout
+        #[arg(long, value_name = "file")]
+        file: Option<String>,
+    },
+    /// Validate subcommand (lint the declarative configs of a catalog, exits with 1 when errors are found)
+    Validate {
+        /// catalog i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// only validate this package (repeat for more packages)
+        #[arg(short, long, value_name = "package")]
+        package: Vec<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Serve subcommand (json rest api over the registered catalogs)
+    Serve {
+        /// address to listen on
+        #[arg(long, value_name = "address", default_value = "127.0.0.1:8080")]
+        address: String,
+    },
+    /// ServeRegistry subcommand (OLM registry grpc api over a registered catalog, as 'opm serve')
+    ServeRegistry {
+        /// catalog i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// address to listen on
+        #[arg(long, value_name = "address", default_value = "127.0.0.1:50051")]
+        address: String,
+    },
+    /// Diff subcommand (compare two registered catalogs)
+    Diff {
+        /// catalog to compare from i.e redhat-operator-index:v4.17 (prefix and fuzzy matching supported, use catalog@snapshot for a snapshot)
+        from: String,
+
+        /// catalog to compare to i.e redhat-operator-index:v4.18 (prefix and fuzzy matching supported, use catalog@snapshot for a snapshot)
+        to: String,
+
+        /// architecture of the catalogs
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format (text, json or markdown)
+        #[arg(short, long, value_enum, default_value = "text")]
+        output: DiffFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum QueryCommands {
+    /// List packages with default channel, channel count and latest bundle
+    Packages {
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+
+        /// only list the packages configured in the ImageSetConfiguration
+        #[arg(short, long)]
+        mirrored: bool,
+    },
+    /// Export the upgrade graph of a package (or a single channel) as graphviz dot or mermaid
+    Graph {
+        /// package name i.e albo-operator
+        package: String,
+
+        /// only export this channel (defaults to all channels of the package)
+        #[arg(long, value_name = "channel")]
+        channel: Option<String>,
+
+        /// graph format
+        #[arg(short, long, value_enum, default_value = "dot")]
+        format: GraphFormat,
+
+        /// write the graph to a file instead of stdout
+        #[arg(long, value_name = "file")]
+        file: Option<String>,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+    },
+    /// Transitive dependencies of a bundle (required packages and gvks resolved against the whole catalog)
+    Dependencies {
+        /// package name i.e albo-operator
+        package: String,
+
+        /// bundle name i.e albo-operator.v1.2.0
+        bundle: String,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Bundles of other packages that depend on a package (required package or a gvk it provides)
+    Dependents {
+        /// package name i.e cert-manager-operator
+        package: String,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Packages and bundles that provide a gvk (olm.gvk) i.e cert-manager.io/v1/Certificate, group/kind or kind
+    Provides {
+        /// gvk to look up (group/version/kind, group/kind or kind, case insensitive)
+        gvk: String,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Shortest supported upgrade path between two bundles of a channel (replaces, skips and skipRange)
+    UpgradePath {
+        /// package name i.e albo-operator
+        package: String,
+
+        /// installed bundle i.e albo-operator.v1.0.0
+        from: String,
+
+        /// target bundle (defaults to the head of the channel)
+        to: Option<String>,
+
+        /// channel to upgrade in (defaults to the default channel of the package)
+        #[arg(long, value_name = "channel")]
+        channel: Option<String>,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum ShowCommands {
+    /// Show every property of a bundle (image, gvks, required packages, related images, csv metadata)
+    Bundle {
+        /// package name i.e albo-operator
+        package: String,
+
+        /// bundle name i.e albo-operator.v1.2.0
+        bundle: String,
+
+        /// catalog to query i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format (text, json or yaml)
+        #[arg(short, long, value_enum, default_value = "text")]
+        output: OutputFormat,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum SnapshotCommands {
+    /// List the snapshots of a catalog (newest first)
+    List {
+        /// catalog i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// output format
+        #[arg(short, long, value_enum, default_value = "table")]
+        output: OutputFormat,
+    },
+    /// Remove snapshots of a catalog according to the retention settings
+    Prune {
+        /// catalog i.e redhat-operator-index:v4.18 (optional if only one catalog is registered)
+        #[arg(short, long, value_name = "catalog")]
+        catalog: Option<String>,
+
+        /// architecture of the catalog
+        #[arg(short, long, value_name = "arch", default_value = "amd64")]
+        arch: Option<String>,
+
+        /// number of snapshots to keep
+        #[arg(short, long, value_name = "keep", default_value = "3")]
+        keep: usize,
+
+        /// remove snapshots older than the given number of days
+        #[arg(long, value_name = "max-age")]
+        max_age: Option<i64>,
     },
 }
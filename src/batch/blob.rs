@@ -0,0 +1,350 @@
+use async_trait::async_trait;
+use chrono::Utc;
+use custom_logger::*;
+use mirror_copy::ImplDownloadImageInterface;
+use mirror_error::MirrorError;
+use reqwest::header::RANGE;
+use reqwest::StatusCode;
+use sha2::{Digest, Sha256};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use tokio::fs::OpenOptions;
+use tokio::io::AsyncWriteExt;
+
+/// blob requests used by the batch worker
+#[async_trait]
+pub trait BlobInterface {
+    /// write a blob to the file, when offset is set the download is resumed with a range request
+    // the file is appended to on partial content and replaced on a full response, a range the
+    // registry can't satisfy means the file already has every byte
+    async fn get_blob_range(
+        &self,
+        client: &reqwest::Client,
+        url: String,
+        token: String,
+        offset: u64,
+        file: PathBuf,
+    ) -> Result<(), MirrorError>;
+}
+
+/// build the http client shared by the blob downloads of a batch
+// certificates are not verified when tls_verify is false (self signed mirror registries)
+pub fn blob_client(tls_verify: bool) -> Result<reqwest::Client, MirrorError> {
+    reqwest::Client::builder()
+        .danger_accept_invalid_certs(!tls_verify)
+        .build()
+        .map_err(|e| MirrorError::new(&format!("creating http client {}", e)))
+}
+
+#[async_trait]
+impl BlobInterface for ImplDownloadImageInterface {
+    async fn get_blob_range(
+        &self,
+        client: &reqwest::Client,
+        url: String,
+        token: String,
+        offset: u64,
+        file: PathBuf,
+    ) -> Result<(), MirrorError> {
+        let mut req = client.get(&url);
+        if !token.is_empty() {
+            req = req.bearer_auth(token);
+        }
+        if offset > 0 {
+            req = req.header(RANGE, format!("bytes={}-", offset));
+        }
+        let mut res = req
+            .send()
+            .await
+            .map_err(|e| MirrorError::new(&format!("downloading blob {} {}", url, e)))?;
+        let append = match res.status() {
+            StatusCode::PARTIAL_CONTENT => true,
+            StatusCode::RANGE_NOT_SATISFIABLE if offset > 0 => return Ok(()),
+            status if status.is_success() => false,
+            status => {
+                return Err(MirrorError::new(&format!(
+                    "downloading blob {} {}",
+                    url, status
+                )))
+            }
+        };
+        let mut output = OpenOptions::new()
+            .create(true)
+            .write(true)
+            .append(append)
+            .truncate(!append)
+            .open(&file)
+            .await
+            .map_err(|e| MirrorError::new(&format!("creating file {:?} {}", file, e)))?;
+        while let Some(chunk) = res
+            .chunk()
+            .await
+            .map_err(|e| MirrorError::new(&format!("downloading blob {} {}", url, e)))?
+        {
+            output
+                .write_all(&chunk)
+                .await
+                .map_err(|e| MirrorError::new(&format!("writing file {:?} {}", file, e)))?;
+        }
+        output
+            .flush()
+            .await
+            .map_err(|e| MirrorError::new(&format!("writing file {:?} {}", file, e)))?;
+        Ok(())
+    }
+}
+
+/// get the path of a blob in the blobs-store (<dir>/<hex[..2]>/<hex>)
+pub fn blob_store_path(dir: &str, blob_sum: &str) -> Result<PathBuf, MirrorError> {
+    match blob_sum.split_once(':') {
+        Some(("sha256", hex)) if hex.len() > 2 && hex.chars().all(|c| c.is_ascii_hexdigit()) => {
+            Ok(Path::new(dir).join(&hex[..2]).join(hex))
+        }
+        _ => Err(MirrorError::new(&format!(
+            "invalid blob digest {}",
+            blob_sum
+        ))),
+    }
+}
+
+/// calculate the sha256 digest of a file
+pub fn file_digest(path: &Path) -> Result<String, MirrorError> {
+    let mut file = fs::File::open(path)
+        .map_err(|e| MirrorError::new(&format!("opening file {:?} {}", path, e)))?;
+    let mut hasher = Sha256::new();
+    io::copy(&mut file, &mut hasher)
+        .map_err(|e| MirrorError::new(&format!("reading file {:?} {}", path, e)))?;
+    Ok(format!("sha256:{}", hex::encode(hasher.finalize())))
+}
+
+/// calculate the digest of a blob on the blocking pool (layers can be hundreds of MB)
+async fn blob_digest(path: PathBuf) -> Result<String, MirrorError> {
+    tokio::task::spawn_blocking(move || file_digest(&path))
+        .await
+        .map_err(|e| MirrorError::new(&format!("calculating blob digest {}", e)))?
+}
+
+/// move a corrupted blob (or partial download) to <dir>/quarantine so it is downloaded again
+// the file is kept (with a timestamp suffix) to be able to investigate registry or proxy issues
+pub fn quarantine_blob(dir: &str, path: &Path) -> Result<PathBuf, MirrorError> {
+    let quarantine_dir = Path::new(dir).join("quarantine");
+    fs::create_dir_all(&quarantine_dir)
+        .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", quarantine_dir, e)))?;
+    let name = path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or_default();
+    let dest = quarantine_dir.join(format!("{}-{}", name, Utc::now().format("%Y%m%d%H%M%S%f")));
+    fs::rename(path, &dest)
+        .map_err(|e| MirrorError::new(&format!("quarantining blob {:?} {}", path, e)))?;
+    warn!(
+        "[quarantine_blob] moved corrupted blob {:?} to {:?}",
+        path, dest
+    );
+    Ok(dest)
+}
+
+/// download a blob to the blobs-store, returns false when the blob was already in the store
+// blobs on disk are only skipped when their sha256 matches (corrupted blobs are quarantined),
+// downloads are written to <hex>.partial and resumed with a range request, when verify_blob is set
+// the digest of the download is checked before it is moved in place
+pub async fn download_blob<T: BlobInterface>(
+    blob_impl: &T,
+    client: &reqwest::Client,
+    dir: &str,
+    url: &str,
+    token: &str,
+    verify_blob: bool,
+    blob_sum: &str,
+) -> Result<bool, MirrorError> {
+    let path = blob_store_path(dir, blob_sum)?;
+    if path.exists() {
+        if blob_digest(path.clone()).await? == blob_sum {
+            trace!("[download_blob] {} exists", blob_sum);
+            return Ok(false);
+        }
+        quarantine_blob(dir, &path)?;
+    }
+    let parent = path.parent().unwrap_or(Path::new(dir));
+    fs::create_dir_all(parent)
+        .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", parent, e)))?;
+    let partial = path.with_extension("partial");
+    let offset = fs::metadata(&partial).map(|m| m.len()).unwrap_or(0);
+    if offset > 0 {
+        debug!("[download_blob] resuming {} from byte {}", blob_sum, offset);
+    }
+    blob_impl
+        .get_blob_range(
+            client,
+            format!("{}{}", url, blob_sum),
+            token.to_string(),
+            offset,
+            partial.clone(),
+        )
+        .await?;
+
+    if verify_blob {
+        let digest = blob_digest(partial.clone()).await?;
+        if digest != blob_sum {
+            quarantine_blob(dir, &partial)?;
+            return Err(MirrorError::new(&format!(
+                "blob digest mismatch, expected {} got {}",
+                blob_sum, digest
+            )));
+        }
+    }
+    fs::rename(&partial, &path)
+        .map_err(|e| MirrorError::new(&format!("renaming file {:?} {}", partial, e)))?;
+    Ok(true)
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn blob_sum(contents: &str) -> String {
+        format!("sha256:{}", hex::encode(Sha256::digest(contents)))
+    }
+
+    #[test]
+    fn download_blob_resume_pass() {
+        let dir = "test-artifacts/blob-resume-test";
+        let mut server = mockito::Server::new();
+        let url = format!("{}/v2/test/test-image/blobs/", server.url());
+        let reg_impl = ImplDownloadImageInterface {};
+        let client = blob_client(true).unwrap();
+        let contents = "0123456789abcdef";
+        let digest = blob_sum(contents);
+        let path = blob_store_path(dir, &digest).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path.with_extension("partial"), &contents[..6]).unwrap();
+        let mock = server
+            .mock(
+                "GET",
+                format!("/v2/test/test-image/blobs/{}", digest).as_str(),
+            )
+            .match_header("range", "bytes=6-")
+            .match_header("authorization", "Bearer token")
+            .with_status(206)
+            .with_body(&contents[6..])
+            .create();
+        let res = aw!(download_blob(
+            &reg_impl, &client, dir, &url, "token", true, &digest
+        ));
+        assert!(res.unwrap());
+        mock.assert();
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        assert!(!path.with_extension("partial").exists());
+        // a complete partial download (range not satisfiable) is verified and moved in place
+        let contents = "complete partial";
+        let digest = blob_sum(contents);
+        let path = blob_store_path(dir, &digest).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path.with_extension("partial"), contents).unwrap();
+        server
+            .mock(
+                "GET",
+                format!("/v2/test/test-image/blobs/{}", digest).as_str(),
+            )
+            .with_status(416)
+            .create();
+        let res = aw!(download_blob(
+            &reg_impl, &client, dir, &url, "", true, &digest
+        ));
+        assert!(res.unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        // blobs on disk are not requested again
+        let res = aw!(download_blob(
+            &reg_impl,
+            &client,
+            dir,
+            "http://127.0.0.1:1/",
+            "",
+            true,
+            &digest
+        ));
+        assert!(!res.unwrap());
+        fs::remove_dir_all(dir).expect("should delete blob test folder");
+    }
+
+    #[test]
+    fn download_blob_quarantine_pass() {
+        let dir = "test-artifacts/blob-quarantine-test";
+        let mut server = mockito::Server::new();
+        let url = format!("{}/v2/test/test-image/blobs/", server.url());
+        let reg_impl = ImplDownloadImageInterface {};
+        let client = blob_client(true).unwrap();
+        // corrupted blob on disk is quarantined and downloaded again
+        let contents = "good contents";
+        let digest = blob_sum(contents);
+        let path = blob_store_path(dir, &digest).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(&path, "corrupted").unwrap();
+        server
+            .mock(
+                "GET",
+                format!("/v2/test/test-image/blobs/{}", digest).as_str(),
+            )
+            .with_status(200)
+            .with_body(contents)
+            .create();
+        let res = aw!(download_blob(
+            &reg_impl, &client, dir, &url, "", true, &digest
+        ));
+        assert!(res.unwrap());
+        assert_eq!(fs::read_to_string(&path).unwrap(), contents);
+        let quarantined = fs::read_dir(Path::new(dir).join("quarantine"))
+            .unwrap()
+            .map(|e| e.unwrap().path())
+            .collect::<Vec<PathBuf>>();
+        assert_eq!(quarantined.len(), 1);
+        assert_eq!(fs::read_to_string(&quarantined[0]).unwrap(), "corrupted");
+        // a download with the wrong digest fails and is quarantined
+        let digest = blob_sum("expected contents");
+        server
+            .mock(
+                "GET",
+                format!("/v2/test/test-image/blobs/{}", digest).as_str(),
+            )
+            .with_status(200)
+            .with_body("tampered contents")
+            .create();
+        let res = aw!(download_blob(
+            &reg_impl, &client, dir, &url, "", true, &digest
+        ));
+        assert!(res.unwrap_err().to_string().contains("digest mismatch"));
+        let path = blob_store_path(dir, &digest).unwrap();
+        assert!(!path.exists());
+        assert!(!path.with_extension("partial").exists());
+        assert_eq!(
+            fs::read_dir(Path::new(dir).join("quarantine"))
+                .unwrap()
+                .count(),
+            2
+        );
+        // registry errors and invalid digests
+        let digest = blob_sum("missing");
+        server
+            .mock(
+                "GET",
+                format!("/v2/test/test-image/blobs/{}", digest).as_str(),
+            )
+            .with_status(404)
+            .create();
+        assert!(aw!(download_blob(
+            &reg_impl, &client, dir, &url, "", true, &digest
+        ))
+        .is_err());
+        assert!(blob_store_path(dir, "sha256:../../etc").is_err());
+        assert!(blob_store_path(dir, "md5:0123").is_err());
+        fs::remove_dir_all(dir).expect("should delete blob test folder");
+    }
+}
@@ -1 +1,2 @@
+pub mod blob;
 pub mod worker;
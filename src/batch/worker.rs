@@ -1,27 +1,29 @@
+use crate::batch::blob::{blob_client, download_blob, BlobInterface};
 use custom_logger::*;
 use futures::stream::FuturesUnordered;
 use futures::stream::StreamExt;
 use mirror_auth::get_token;
 use mirror_auth::ImplTokenInterface;
-use mirror_copy::DownloadImageInterface;
 use mirror_error::MirrorError;
 use mirror_utils::FsLayer;
 use std::collections::HashMap;
 
-pub async fn execute_batch<T: DownloadImageInterface + Clone>(
+pub async fn execute_batch<T: BlobInterface + Clone>(
     reg_impl: T,
     dir: String,
     verify_blob: bool,
     tls_verify: bool,
     map_in: HashMap<String, Vec<FsLayer>>,
 ) -> Result<(), MirrorError> {
-    let mut futs = FuturesUnordered::new();
     let batch_size = 8;
     let bar = "% completed    [--------------------------------------------------------------]"
         .to_string();
     let t_impl = ImplTokenInterface {};
+    let client = blob_client(tls_verify)?;
+    let mut futs = FuturesUnordered::new();
+    let mut skipped = 0;
     // get blobs in batch of 8
-    // each future handles get_blobs api call
+    // each future downloads (or resumes) a blob, blobs already on disk are skipped
     // batch the calls
     for (k, v) in map_in.clone() {
         let hld: &str;
@@ -34,7 +36,6 @@ pub async fn execute_batch<T: DownloadImageInterface + Clone>(
         } else {
             hld = k.split("http://").nth(1).unwrap();
         }
-        // TODO: add check to see if blobs exist on disk
         let registry = hld.split("/").nth(0).unwrap();
         trace!("url {}", k);
         let token = get_token(
@@ -50,15 +51,13 @@ pub async fn execute_batch<T: DownloadImageInterface + Clone>(
             info!("[execute_batch] downloading {} blobs", v.len());
         }
         for layer in v.iter() {
-            futs.push(reg_impl.get_blob(
-                dir.clone(),
-                url.clone(),
-                token.clone(),
-                verify_blob,
-                layer.blob_sum.clone(),
-            ));
-            if futs.len() >= batch_size {
-                futs.next().await.unwrap()?;
+            let (reg_impl, client, dir) = (&reg_impl, &client, &dir);
+            let (url, token, blob_sum) = (url.clone(), token.clone(), layer.blob_sum.clone());
+            futs.push(async move {
+                download_blob(reg_impl, client, dir, &url, &token, verify_blob, &blob_sum).await
+            });
+            if futs.len() >= batch_size && !futs.next().await.unwrap()? {
+                skipped += 1;
             }
             count += 1;
             if count % 10 == 0 {
@@ -69,9 +68,17 @@ pub async fn execute_batch<T: DownloadImageInterface + Clone>(
         }
         // Wait for the remaining to finish.
         while let Some(response) = futs.next().await {
-            response.unwrap()
+            if !response? {
+                skipped += 1;
+            }
         }
     }
+    if skipped > 0 {
+        info!(
+            "[execute_batch] skipped {} blobs found in the blobs-store",
+            skipped
+        );
+    }
     // Wait for the remaining to finish.
     for (_k, v) in map_in {
         if v.len() > 0 {
@@ -89,13 +96,14 @@ mod tests {
 
     // this brings everything from parent's scope into this scope
     use super::*;
+    use sha2::{Digest, Sha256};
     use std::fs;
     #[test]
     fn execute_batch_pass() {
-        fs::create_dir_all("./test-artifacts/blobs-store/01")
-            .expect("should create blobs-store test folder");
+        let dir = "test-artifacts/batch-test/blobs-store";
+        fs::create_dir_all(dir).expect("should create blobs-store test folder");
 
-        // we set up a mock server for the auth-credentials
+        // we set up a mock server for the auth-credentials and blobs
         let mut server = mockito::Server::new();
         let url = server.url();
 
@@ -138,11 +146,32 @@ mod tests {
 
         let mut map: HashMap<String, Vec<FsLayer>> = HashMap::new();
         let mut vec_fslayer: Vec<FsLayer> = Vec::new();
+        let mut mocks = vec![];
         for x in 0..30 {
+            let contents = format!("blob contents {:0>2}", x);
+            let blob_sum = format!("sha256:{}", hex::encode(Sha256::digest(&contents)));
+            // the first blob is already in the blobs-store and should not be requested
+            let expected = if x == 0 { 0 } else { 1 };
+            if x == 0 {
+                let path = crate::batch::blob::blob_store_path(dir, &blob_sum).unwrap();
+                fs::create_dir_all(path.parent().unwrap()).unwrap();
+                fs::write(&path, &contents).unwrap();
+            }
+            mocks.push(
+                server
+                    .mock(
+                        "GET",
+                        format!("/v2/test/test-image/blobs/{}", blob_sum).as_str(),
+                    )
+                    .with_status(200)
+                    .with_body(&contents)
+                    .expect(expected)
+                    .create(),
+            );
             let fslayer = FsLayer {
-                blob_sum: format!("sha256:0123456789ABCDEF{:0>2}", x),
+                blob_sum,
                 original_ref: Some(format!("{}/test/test-image", url)),
-                size: Some(1234),
+                size: Some(contents.len() as i64),
             };
             vec_fslayer.insert(0, fslayer.clone());
         }
@@ -155,12 +184,19 @@ mod tests {
         let fake = ImplDownloadImageInterface {};
         let res = aw!(execute_batch(
             fake.clone(),
-            "test-artifacts/".to_string(),
-            false,
+            dir.to_string(),
+            true,
             false,
             map.clone()
         ));
         assert_eq!(res.is_ok(), true);
+        for mock in mocks.iter() {
+            mock.assert();
+        }
+        for layer in vec_fslayer.iter() {
+            let path = crate::batch::blob::blob_store_path(dir, &layer.blob_sum).unwrap();
+            assert!(path.exists());
+        }
         // simulate an error
         let fslayer_err = FsLayer {
             blob_sum: format!("0123456789ABCDEF00"),
@@ -175,8 +211,8 @@ mod tests {
         info!("executing batch worker [should fail]");
         let res_err = aw!(execute_batch(
             fake.clone(),
-            "test-artifacts/".to_string(),
-            false,
+            dir.to_string(),
+            true,
             false,
             map.clone()
         ));
@@ -184,7 +220,7 @@ mod tests {
             error!("result -> {}", res_err.as_ref().err().unwrap().to_string());
         }
         assert_eq!(res_err.is_err(), true);
-        fs::remove_dir_all("./test-artifacts/blobs-store")
+        fs::remove_dir_all("./test-artifacts/batch-test")
             .expect("should delete blobs-store test folder");
     }
 }
@@ -1,35 +1,508 @@
-use custom_logger::error;
+use crate::operator::filter::PackageFilter;
+use chrono::{DateTime, Utc};
+use custom_logger::*;
+use mirror_error::MirrorError;
 use serde_derive::{Deserialize, Serialize};
-use std::collections::HashMap;
+use sha2::{Digest, Sha256};
+use std::collections::{BTreeMap, HashMap};
+use std::env;
 use std::fs;
 use std::path::Path;
 use std::process;
 
-#[derive(Serialize, Deserialize, Debug)]
-pub struct ViewConfig {}
+/// current version of the state file schema
+pub const STATE_VERSION: u32 = 1;
+/// environment variable used to override the state file location
+pub const STATE_FILE_ENV: &str = "OPERATOR_CATALOG_VIEWER_STATE";
+/// flat catalog map written by earlier versions in the current directory
+pub const LEGACY_CONFIG: &str = "config.json";
 
-impl ViewConfig {
+/// the catalog registry (persisted as the state file)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ViewState {
+    pub version: u32,
+    // keyed by catalog name:version
+    pub catalogs: BTreeMap<String, CatalogState>,
+}
+
+/// state for a single catalog image
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogState {
+    pub image: String,
+    pub digest: Option<String>,
+    pub last_update: Option<DateTime<Utc>>,
+    pub architectures: BTreeMap<String, ArchState>,
+    // packages filter from the ImageSetConfiguration (empty for the full catalog)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageFilter>,
+}
+
+/// state for a single architecture of a catalog image
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ArchState {
+    pub digest: Option<String>,
+    pub configs_dir: String,
+    pub last_update: Option<DateTime<Utc>>,
+    // previous contents of the tag (newest first)
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub snapshots: Vec<SnapshotState>,
+}
+
+/// archived content of a previous update of an architecture
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotState {
+    pub id: String,
+    pub digest: Option<String>,
+    // snapshot root directory (removed when pruned)
+    pub path: String,
+    pub configs_dir: String,
+    pub last_update: Option<DateTime<Utc>>,
+    pub archived: DateTime<Utc>,
+}
+
+#[derive(Serialize, Deserialize, Debug, Clone)]
+pub struct ViewConfig {
+    pub state_file: String,
+}
+
+impl Default for ViewState {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl ViewState {
     pub fn new() -> Self {
-        Self {}
+        Self {
+            version: STATE_VERSION,
+            catalogs: BTreeMap::new(),
+        }
+    }
+
+    /// record (or refresh) an architecture of a catalog
+    pub fn update_catalog(
+        &mut self,
+        key: String,
+        image: String,
+        digest: Option<String>,
+        arch: String,
+        arch_digest: Option<String>,
+        configs_dir: String,
+    ) {
+        let now = Utc::now();
+        let catalog = self.catalogs.entry(key).or_insert(CatalogState {
+            image: image.clone(),
+            digest: None,
+            last_update: None,
+            architectures: BTreeMap::new(),
+            packages: vec![],
+        });
+        catalog.image = image;
+        catalog.digest = digest;
+        catalog.last_update = Some(now);
+        let snapshots = catalog
+            .architectures
+            .get(&arch)
+            .map(|a| a.snapshots.clone())
+            .unwrap_or_default();
+        catalog.architectures.insert(
+            arch,
+            ArchState {
+                digest: arch_digest,
+                configs_dir,
+                last_update: Some(now),
+                snapshots,
+            },
+        );
+    }
+
+    /// get an architecture of a catalog
+    pub fn get_arch(&self, key: &str, arch: &str) -> Option<&ArchState> {
+        self.catalogs.get(key)?.architectures.get(arch)
+    }
+
+    /// get an architecture of a catalog (mutable)
+    pub fn get_arch_mut(&mut self, key: &str, arch: &str) -> Option<&mut ArchState> {
+        self.catalogs.get_mut(key)?.architectures.get_mut(arch)
+    }
+
+    /// record a snapshot of an architecture (newest first)
+    pub fn add_snapshot(&mut self, key: &str, arch: &str, snapshot: SnapshotState) {
+        if let Some(a) = self.get_arch_mut(key, arch) {
+            a.snapshots.insert(0, snapshot);
+        }
+    }
+
+    /// find a snapshot of a catalog (name:version/arch)
+    // the id is the position (1 is the newest) or a prefix of the snapshot id or digest
+    pub fn find_snapshot(&self, catalog: &str, id: &str) -> Result<SnapshotState, MirrorError> {
+        let (key, arch) = catalog.split_once('/').unwrap_or((catalog, "amd64"));
+        let snapshots = self
+            .get_arch(key, arch)
+            .map(|a| a.snapshots.clone())
+            .unwrap_or_default();
+        let id = id.trim();
+        let found = match id.parse::<usize>() {
+            Ok(pos) if pos > 0 && pos <= snapshots.len() => vec![snapshots[pos - 1].clone()],
+            _ => snapshots
+                .iter()
+                .filter(|s| {
+                    let digest = s.digest.as_deref().unwrap_or_default();
+                    s.id.starts_with(id)
+                        || digest.starts_with(id)
+                        || digest.trim_start_matches("sha256:").starts_with(id)
+                })
+                .cloned()
+                .collect(),
+        };
+        match found.len() {
+            1 => Ok(found[0].clone()),
+            n => {
+                let choices = snapshots
+                    .iter()
+                    .enumerate()
+                    .map(|(i, s)| format!("  {}) {}", i + 1, s.id))
+                    .collect::<Vec<String>>();
+                let choices = match choices.is_empty() {
+                    true => "  (none)".to_string(),
+                    false => choices.join("\n"),
+                };
+                Err(MirrorError::new(&format!(
+                    "snapshot \"{}\" of {} {}, valid choices are :\n{}",
+                    id,
+                    catalog,
+                    if n == 0 { "not found" } else { "is ambiguous" },
+                    choices
+                )))
+            }
+        }
+    }
+
+    /// set the packages filter of a catalog
+    pub fn set_packages(&mut self, key: &str, packages: Vec<PackageFilter>) {
+        if let Some(catalog) = self.catalogs.get_mut(key) {
+            catalog.packages = packages;
+        }
+    }
+
+    /// flatten the catalogs to name:version/arch -> packages filter
+    pub fn packages_map(&self) -> HashMap<String, Vec<PackageFilter>> {
+        let mut map: HashMap<String, Vec<PackageFilter>> = HashMap::new();
+        for (k, v) in self.catalogs.iter() {
+            for arch in v.architectures.keys() {
+                map.insert(format!("{}/{}", k, arch), v.packages.clone());
+            }
+        }
+        map
+    }
+
+    /// flatten the catalogs to name:version/arch -> catalog image
+    pub fn images_map(&self) -> HashMap<String, String> {
+        let mut map: HashMap<String, String> = HashMap::new();
+        for (k, v) in self.catalogs.iter() {
+            for arch in v.architectures.keys() {
+                map.insert(format!("{}/{}", k, arch), v.image.clone());
+            }
+        }
+        map
+    }
+
+    /// flatten the catalogs to name:version/arch -> configs dir
+    pub fn configs_map(&self) -> HashMap<String, String> {
+        let mut map: HashMap<String, String> = HashMap::new();
+        for (k, v) in self.catalogs.iter() {
+            for (arch, a) in v.architectures.iter() {
+                map.insert(format!("{}/{}", k, arch), a.configs_dir.clone());
+            }
+        }
+        map
     }
 
-    pub fn read_config(&self) -> HashMap<String, String> {
-        let is_present = Path::new("config.json").exists();
-        if is_present {
-            let contents = fs::read_to_string("config.json");
-            if contents.is_err() {
-                error!("could not read config.json - did you execute an 'update'?");
+    /// migrate the flat map from the legacy config.json
+    // legacy keys are name:version (amd64) or name:version/arch
+    pub fn from_legacy(map: HashMap<String, String>) -> Self {
+        let mut state = ViewState::new();
+        for (k, v) in map.iter() {
+            let (name, arch) = k.split_once('/').unwrap_or((k, "amd64"));
+            let catalog = state
+                .catalogs
+                .entry(name.to_string())
+                .or_insert(CatalogState {
+                    image: name.to_string(),
+                    digest: None,
+                    last_update: None,
+                    architectures: BTreeMap::new(),
+                    packages: vec![],
+                });
+            // legacy paths are relative to the directory config.json was written in
+            let configs_dir = fs::canonicalize(v)
+                .map(|p| format!("{}/", p.to_string_lossy()))
+                .unwrap_or(v.clone());
+            catalog.architectures.insert(
+                arch.to_string(),
+                ArchState {
+                    digest: None,
+                    configs_dir,
+                    last_update: None,
+                    snapshots: vec![],
+                },
+            );
+        }
+        state
+    }
+}
+
+impl ViewConfig {
+    /// resolve the state file location
+    // order is --state-file flag, OPERATOR_CATALOG_VIEWER_STATE env var,
+    // $XDG_DATA_HOME/operator-catalog-viewer/state.json and finally $HOME/.local/share
+    pub fn new(state_file: Option<String>) -> Self {
+        let state_file = match state_file {
+            Some(file) => file,
+            None => match env::var(STATE_FILE_ENV) {
+                Ok(file) if !file.is_empty() => file,
+                _ => {
+                    let data_dir = match env::var("XDG_DATA_HOME") {
+                        Ok(dir) if !dir.is_empty() => dir,
+                        _ => format!(
+                            "{}/.local/share",
+                            env::var("HOME").unwrap_or(".".to_string())
+                        ),
+                    };
+                    format!("{}/operator-catalog-viewer/state.json", data_dir)
+                }
+            },
+        };
+        Self { state_file }
+    }
+
+    /// default directory of the files exported from the viewer (exports/ next to the state file)
+    pub fn export_dir(&self) -> String {
+        Path::new(&self.state_file)
+            .parent()
+            .unwrap_or(Path::new("."))
+            .join("exports")
+            .to_string_lossy()
+            .to_string()
+    }
+
+    /// read the state file (migrates a legacy config.json if found)
+    pub fn read_state(&self) -> Result<ViewState, MirrorError> {
+        if !Path::new(&self.state_file).exists() {
+            if Path::new(LEGACY_CONFIG).exists() {
+                info!(
+                    "[read_state] migrating {} to {}",
+                    LEGACY_CONFIG, self.state_file
+                );
+                let contents = fs::read_to_string(LEGACY_CONFIG)
+                    .map_err(|e| MirrorError::new(&format!("reading {} {}", LEGACY_CONFIG, e)))?;
+                let map: HashMap<String, String> = serde_json::from_str(&contents)
+                    .map_err(|e| MirrorError::new(&format!("parsing {} {}", LEGACY_CONFIG, e)))?;
+                let state = ViewState::from_legacy(map);
+                self.write_state(&state)?;
+                return Ok(state);
+            }
+            return Ok(ViewState::new());
+        }
+        let contents = fs::read_to_string(&self.state_file)
+            .map_err(|e| MirrorError::new(&format!("reading {} {}", self.state_file, e)))?;
+        let state: ViewState = serde_json::from_str(&contents)
+            .map_err(|e| MirrorError::new(&format!("parsing {} {}", self.state_file, e)))?;
+        if state.version > STATE_VERSION {
+            return Err(MirrorError::new(&format!(
+                "state file {} has version {} (supported {}) - please upgrade",
+                self.state_file, state.version, STATE_VERSION
+            )));
+        }
+        Ok(state)
+    }
+
+    /// write the state file (creates the parent directory if needed)
+    pub fn write_state(&self, state: &ViewState) -> Result<(), MirrorError> {
+        if let Some(parent) = Path::new(&self.state_file).parent() {
+            if !parent.as_os_str().is_empty() {
+                fs::create_dir_all(parent).map_err(|e| {
+                    MirrorError::new(&format!("creating directory {:?} {}", parent, e))
+                })?;
+            }
+        }
+        let json = serde_json::to_string_pretty(state)
+            .map_err(|e| MirrorError::new(&format!("serializing state {}", e)))?;
+        fs::write(&self.state_file, json.as_bytes())
+            .map_err(|e| MirrorError::new(&format!("writing {} {}", self.state_file, e)))?;
+        Ok(())
+    }
+
+    /// read the state file, exits on error
+    pub fn read_state_or_exit(&self) -> ViewState {
+        match self.read_state() {
+            Ok(state) => state,
+            Err(err) => {
+                error!(
+                    "could not read state file {} - did you execute an 'update'?",
+                    err
+                );
                 process::exit(1);
             }
-            serde_json::from_str(&contents.unwrap()).unwrap()
-        } else {
-            let res: HashMap<String, String> = HashMap::new();
-            res
         }
     }
+}
+
+/// calculate the sha256 digest of the manifest content
+pub fn get_digest(content: String) -> String {
+    let mut hasher = Sha256::new();
+    hasher.update(content.as_bytes());
+    format!("sha256:{}", hex::encode(hasher.finalize()))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn view_state_pass() {
+        let mut state = ViewState::new();
+        state.update_catalog(
+            "redhat-operator-index:v4.18".to_string(),
+            "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+            Some("sha256:1234".to_string()),
+            "amd64".to_string(),
+            Some("sha256:5678".to_string()),
+            "/tmp/amd64/configs/".to_string(),
+        );
+        state.update_catalog(
+            "redhat-operator-index:v4.18".to_string(),
+            "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+            Some("sha256:1234".to_string()),
+            "arm64".to_string(),
+            Some("sha256:9012".to_string()),
+            "/tmp/arm64/configs/".to_string(),
+        );
+        assert_eq!(state.catalogs.len(), 1);
+        let map = state.configs_map();
+        assert_eq!(map.len(), 2);
+        assert_eq!(
+            map.get("redhat-operator-index:v4.18/arm64").unwrap(),
+            "/tmp/arm64/configs/"
+        );
+    }
+
+    #[test]
+    fn find_snapshot_pass() {
+        let mut state = ViewState::new();
+        let key = "redhat-operator-index:v4.18";
+        state.update_catalog(
+            key.to_string(),
+            "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+            None,
+            "amd64".to_string(),
+            Some("sha256:9999".to_string()),
+            "/tmp/cache/configs/".to_string(),
+        );
+        for (id, digest) in [
+            ("20250101T000000Z-1111", "1111"),
+            ("20250201T000000Z-2222", "2222"),
+        ] {
+            state.add_snapshot(
+                key,
+                "amd64",
+                SnapshotState {
+                    id: id.to_string(),
+                    digest: Some(format!("sha256:{}", digest)),
+                    path: format!("/tmp/snapshots/{}", id),
+                    configs_dir: format!("/tmp/snapshots/{}/configs/", id),
+                    last_update: None,
+                    archived: Utc::now(),
+                },
+            );
+        }
+        // snapshots are kept when the catalog is updated again
+        state.update_catalog(
+            key.to_string(),
+            "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+            None,
+            "amd64".to_string(),
+            Some("sha256:9999".to_string()),
+            "/tmp/cache/configs/".to_string(),
+        );
+        let catalog = "redhat-operator-index:v4.18/amd64";
+        assert_eq!(
+            state.find_snapshot(catalog, "1").unwrap().id,
+            "20250201T000000Z-2222"
+        );
+        assert_eq!(
+            state.find_snapshot(catalog, "sha256:1111").unwrap().id,
+            "20250101T000000Z-1111"
+        );
+        assert_eq!(
+            state.find_snapshot(catalog, "202502").unwrap().id,
+            "20250201T000000Z-2222"
+        );
+        let res = state.find_snapshot(catalog, "2025");
+        assert!(res.err().unwrap().to_string().contains("is ambiguous"));
+        let res = state.find_snapshot(catalog, "3");
+        assert!(res
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("  1) 20250201T000000Z-2222"));
+    }
+
+    #[test]
+    fn from_legacy_pass() {
+        let mut map: HashMap<String, String> = HashMap::new();
+        map.insert(
+            "redhat-operator-index:v4.16".to_string(),
+            "/tmp/v4.16/configs/".to_string(),
+        );
+        map.insert(
+            "redhat-operator-index:v4.18/arm64".to_string(),
+            "/tmp/v4.18/configs/".to_string(),
+        );
+        let state = ViewState::from_legacy(map);
+        assert_eq!(state.version, STATE_VERSION);
+        let configs = state.configs_map();
+        assert_eq!(
+            configs.get("redhat-operator-index:v4.16/amd64").unwrap(),
+            "/tmp/v4.16/configs/"
+        );
+        assert_eq!(
+            configs.get("redhat-operator-index:v4.18/arm64").unwrap(),
+            "/tmp/v4.18/configs/"
+        );
+    }
+
+    #[test]
+    fn read_write_state_pass() {
+        let state_file = "test-artifacts/state-test/state.json".to_string();
+        let cfg_impl = ViewConfig::new(Some(state_file.clone()));
+        let mut state = ViewState::new();
+        state.update_catalog(
+            "test-index-operator:v1.0".to_string(),
+            "localhost/test/test-index-operator:v1.0".to_string(),
+            None,
+            "amd64".to_string(),
+            None,
+            "/tmp/configs/".to_string(),
+        );
+        cfg_impl
+            .write_state(&state)
+            .expect("should write state file");
+        let res = cfg_impl.read_state().expect("should read state file");
+        assert_eq!(res, state);
+        fs::remove_dir_all("test-artifacts/state-test").expect("should delete state test folder");
+    }
 
-    pub fn write_config(&self, map: HashMap<String, String>) {
-        let json = serde_json::to_string(&map).unwrap();
-        fs::write("config.json", json.as_bytes()).expect("should create config file");
+    #[test]
+    fn get_digest_pass() {
+        assert_eq!(
+            get_digest("".to_string()),
+            "sha256:e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+        );
     }
 }
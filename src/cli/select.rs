@@ -0,0 +1,161 @@
+use mirror_error::MirrorError;
+
+/// get the registered catalogs for the given architecture (sorted)
+// entries without an architecture suffix were registered as amd64
+pub fn filter_catalogs_by_arch(keys: Vec<String>, arch: &str) -> Vec<String> {
+    let mut catalogs = keys
+        .into_iter()
+        .filter(|k| {
+            let (_, k_arch) = k.split_once('/').unwrap_or((k, "amd64"));
+            k_arch == arch
+        })
+        .collect::<Vec<String>>();
+    catalogs.sort();
+    catalogs
+}
+
+/// find the catalog that matches the query
+// tries an exact match first, then a prefix match and finally a fuzzy (subsequence) match
+pub fn find_catalog(catalogs: &[String], query: &str) -> Result<String, MirrorError> {
+    let query = query.trim().to_lowercase();
+    let matchers: [fn(&str, &str) -> bool; 3] = [
+        |name, q| name == q,
+        |name, q| name.starts_with(q),
+        |name, q| is_subsequence(name, q),
+    ];
+    for matcher in matchers.iter() {
+        let found = catalogs
+            .iter()
+            .filter(|c| {
+                let full = c.to_lowercase();
+                let (name, _) = full.split_once('/').unwrap_or((&full, ""));
+                matcher(&full, &query) || matcher(name, &query)
+            })
+            .cloned()
+            .collect::<Vec<String>>();
+        match found.len() {
+            0 => continue,
+            1 => return Ok(found[0].clone()),
+            _ => {
+                return Err(MirrorError::new(&format!(
+                    "catalog \"{}\" is ambiguous, it matches :\n{}",
+                    query,
+                    list_choices(&found)
+                )))
+            }
+        }
+    }
+    Err(MirrorError::new(&format!(
+        "catalog \"{}\" not found, valid choices are :\n{}",
+        query,
+        list_choices(catalogs)
+    )))
+}
+
+/// resolve the catalog to use (the query is optional when only one catalog is registered)
+pub fn resolve_catalog(catalogs: &[String], query: Option<&str>) -> Result<String, MirrorError> {
+    match query {
+        Some(q) => find_catalog(catalogs, q),
+        None if catalogs.len() == 1 => Ok(catalogs[0].clone()),
+        None => Err(MirrorError::new(&format!(
+            "please select a catalog with --catalog, valid choices are :\n{}",
+            list_choices(catalogs)
+        ))),
+    }
+}
+
+/// format the catalogs as an indented list
+pub fn list_choices(catalogs: &[String]) -> String {
+    if catalogs.is_empty() {
+        return "  (none) - did you execute an 'update'?".to_string();
+    }
+    catalogs
+        .iter()
+        .map(|c| format!("  {}", c))
+        .collect::<Vec<String>>()
+        .join("\n")
+}
+
+/// check if all characters of the query appear in order in the name
+fn is_subsequence(name: &str, query: &str) -> bool {
+    let mut chars = name.chars();
+    query.chars().all(|q| chars.any(|c| c == q))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    fn catalogs() -> Vec<String> {
+        filter_catalogs_by_arch(
+            vec![
+                "redhat-operator-index:v4.16/amd64".to_string(),
+                "redhat-operator-index:v4.18/amd64".to_string(),
+                "redhat-operator-index:v4.18/arm64".to_string(),
+                "certified-operator-index:v4.16".to_string(),
+            ],
+            "amd64",
+        )
+    }
+
+    #[test]
+    fn filter_catalogs_by_arch_pass() {
+        let res = catalogs();
+        assert_eq!(res.len(), 3);
+        assert_eq!(res[0], "certified-operator-index:v4.16");
+    }
+
+    #[test]
+    fn find_catalog_pass() {
+        let c = catalogs();
+        // exact match with and without the arch suffix
+        assert_eq!(
+            find_catalog(&c, "redhat-operator-index:v4.18").unwrap(),
+            "redhat-operator-index:v4.18/amd64"
+        );
+        assert_eq!(
+            find_catalog(&c, "redhat-operator-index:v4.16/amd64").unwrap(),
+            "redhat-operator-index:v4.16/amd64"
+        );
+        // prefix
+        assert_eq!(
+            find_catalog(&c, "certified").unwrap(),
+            "certified-operator-index:v4.16"
+        );
+        // fuzzy
+        assert_eq!(
+            find_catalog(&c, "rhv418").unwrap(),
+            "redhat-operator-index:v4.18/amd64"
+        );
+    }
+
+    #[test]
+    fn resolve_catalog_pass() {
+        let c = catalogs();
+        assert!(resolve_catalog(&c, None).is_err());
+        assert_eq!(
+            resolve_catalog(&c[..1], None).unwrap(),
+            "certified-operator-index:v4.16"
+        );
+        assert_eq!(
+            resolve_catalog(&c, Some("certified")).unwrap(),
+            "certified-operator-index:v4.16"
+        );
+    }
+
+    #[test]
+    fn find_catalog_fail() {
+        let c = catalogs();
+        let ambiguous = find_catalog(&c, "redhat");
+        assert!(ambiguous.is_err());
+        assert!(ambiguous.err().unwrap().to_string().contains("ambiguous"));
+        let missing = find_catalog(&c, "community");
+        assert!(missing.is_err());
+        assert!(missing
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("  redhat-operator-index:v4.18/amd64"));
+    }
+}
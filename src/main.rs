@@ -1,4 +1,6 @@
-use crate::cli::config::ViewConfig;
+use crate::cli::config::{ViewConfig, ViewState};
+use crate::cli::select::*;
+use chrono::Utc;
 use clap::Parser;
 use color_eyre::config::HookBuilder;
 use crossterm::{
@@ -12,10 +14,13 @@ use mirror_copy::ImplDownloadImageInterface;
 use mirror_error::MirrorError;
 use ratatui::prelude::*;
 use std::collections::HashMap;
+use std::fs;
 use std::io;
 use std::io::stdout;
+use std::net::SocketAddr;
 use std::process;
 use std::str::FromStr;
+use std::sync::Arc;
 use tokio;
 
 // define local modules
@@ -23,10 +28,34 @@ mod api;
 mod batch;
 mod cli;
 mod operator;
+mod query;
+mod server;
 mod ui;
 
 use api::schema::*;
+use operator::catalog::get_package_config;
 use operator::collector::*;
+use operator::dependency::*;
+use operator::dependents::ReverseIndex;
+use operator::filter::PackageFilter;
+use operator::import::import_operator_catalog;
+use operator::local::*;
+use operator::provides::GvkIndex;
+use operator::selection::*;
+use operator::snapshot::*;
+use query::bundle::*;
+use query::dependencies::*;
+use query::dependents::*;
+use query::diff::*;
+use query::graph::*;
+use query::imageset::*;
+use query::packages::*;
+use query::provides::*;
+use query::snapshots::*;
+use query::upgrade::*;
+use query::validate::*;
+use server::grpc::RegistryService;
+use server::rest::*;
 use ui::render::*;
 
 // main entry point (use async)
@@ -50,6 +79,8 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
             working_dir,
             config_file,
             all_arch,
+            keep_snapshots,
+            max_snapshot_age,
         }) => {
             info!("[main] operator-catalog-viewer {} ", config_file.clone());
 
@@ -71,9 +102,14 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                     get_operator_catalog(
                         reg_con.clone(),
                         working_dir.clone(),
-                        false,
+                        all_arch.unwrap_or(false),
                         true,
                         isc_config.mirror.operators.unwrap(),
+                        ViewConfig::new(args.state_file.clone()),
+                        Retention {
+                            keep: *keep_snapshots,
+                            max_age_days: *max_snapshot_age,
+                        },
                     )
                     .await?;
                 }
@@ -81,10 +117,36 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 error!("{}", config.err().unwrap());
             }
         }
+        Some(Commands::Import {
+            working_dir,
+            path,
+            image,
+            all_arch,
+            keep_snapshots,
+            max_snapshot_age,
+        }) => {
+            let key = import_operator_catalog(
+                path.clone(),
+                image.clone(),
+                working_dir.clone(),
+                all_arch.unwrap_or(false),
+                ViewConfig::new(args.state_file.clone()),
+                Retention {
+                    keep: *keep_snapshots,
+                    max_age_days: *max_snapshot_age,
+                },
+            )
+            .await?;
+            info!("[main] imported catalog {}", key);
+        }
         Some(Commands::View {
             configs_dir,
             dev_enable,
             operator,
+            arch,
+            catalog,
+            snapshot,
+            export_dir,
         }) => {
             if dev_enable.is_some() {
                 debug!("[main] (dev-mode) operator {:?}", operator);
@@ -106,43 +168,343 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
                 process::exit(0);
             }
 
-            let mut count = 1;
-            let mut in_map: HashMap<usize, String> = HashMap::new();
-            let cfg_impl = ViewConfig::new();
-            let map = cfg_impl.read_config();
-            info!(
-                " Please select a catalog you would like to view (use the number and press enter)\n"
-            );
-            for (k, v) in map.iter() {
-                let data = format!("{}) {}", count, k);
-                println!(" {}", data);
-                in_map.insert(count, k.to_lowercase());
-                count += 1;
-            }
-            println!("");
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let map = state.configs_map();
+            let arch = arch.as_deref().unwrap_or("amd64");
+            let catalogs = filter_catalogs_by_arch(map.keys().cloned().collect(), arch);
+            // a local fbc directory is viewed without being registered
+            let local = match catalog.as_deref().filter(|c| is_fbc_dir(c)) {
+                Some(dir) => Some(open_fbc_dir(dir)?),
+                None => None,
+            };
 
-            let mut input_line = String::new();
-            io::stdin()
-                .read_line(&mut input_line)
-                .expect("failed to read line");
+            let value = match (&local, catalog) {
+                (Some((name, _)), _) => name.clone(),
+                (None, Some(query)) => match find_catalog(&catalogs, query) {
+                    Ok(value) => value,
+                    Err(err) => {
+                        error!("[main] {}", err);
+                        process::exit(1);
+                    }
+                },
+                (None, None) => {
+                    let mut in_map: HashMap<usize, String> = HashMap::new();
+                    info!(
+                        " Please select a catalog you would like to view (use the number and press enter)\n"
+                    );
+                    for (count, k) in catalogs.iter().enumerate() {
+                        println!(" {}) {}", count + 1, k);
+                        in_map.insert(count + 1, k.clone());
+                    }
+                    println!();
 
-            let res = input_line.trim().parse();
-            if res.is_err() {
-                error!("could not parse number");
-                process::exit(1);
+                    let mut input_line = String::new();
+                    io::stdin()
+                        .read_line(&mut input_line)
+                        .expect("failed to read line");
+
+                    let res = input_line.trim().parse::<usize>();
+                    match res.ok().and_then(|n| in_map.get(&n)) {
+                        Some(value) => value.clone(),
+                        None => {
+                            error!(
+                                "[main] invalid selection \"{}\", valid choices are :\n{}",
+                                input_line.trim(),
+                                list_choices(&catalogs)
+                            );
+                            process::exit(1);
+                        }
+                    }
+                }
+            };
+            let mut catalog_map = catalogs
+                .iter()
+                .map(|k| (k.clone(), map.get(k).unwrap().clone()))
+                .collect::<HashMap<String, String>>();
+            if let Some((name, configs_dir)) = local {
+                catalog_map.insert(name, configs_dir);
             }
-            let value = in_map.get(&res.unwrap()).unwrap();
-            let configs_dir = map.get(value);
+            let mut packages_map = state.packages_map();
+            let value = match snapshot {
+                Some(id) => match state.find_snapshot(&value, id) {
+                    Ok(snap) => {
+                        // the snapshot is added as an extra catalog so it can be switched to
+                        let key = format!("{}@{}", value, snap.id);
+                        catalog_map.insert(key.clone(), snap.configs_dir);
+                        let filters = packages_map.get(&value).cloned().unwrap_or_default();
+                        packages_map.insert(key.clone(), filters);
+                        key
+                    }
+                    Err(err) => {
+                        error!("[main] {}", err);
+                        process::exit(1);
+                    }
+                },
+                None => value,
+            };
+            let configs_dir = catalog_map.get(&value);
 
             init_error_hooks()?;
             let mut terminal = init_terminal()?;
-            let mut app = App::new(value.to_lowercase(), configs_dir.unwrap().to_lowercase());
+            let mut app = App::new(
+                value,
+                configs_dir.unwrap().clone(),
+                catalog_map,
+                packages_map,
+            );
+            app.catalog_images = state.images_map();
+            app.export_dir = export_dir.clone().unwrap_or(cfg_impl.export_dir());
             let res = run_app(&mut terminal, &mut app);
             restore_terminal()?;
             if let Err(err) = res {
                 println!("{err:?}");
             }
         }
+        Some(Commands::Query { query }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            match query {
+                QueryCommands::Packages {
+                    catalog,
+                    arch,
+                    output,
+                    mirrored,
+                } => {
+                    let (_, configs_dir, filters) = select_catalog(&state, catalog, arch);
+                    let filters = if *mirrored { filters } else { vec![] };
+                    let summaries = query_packages(&configs_dir, &filters)?;
+                    println!("{}", format_packages(&summaries, output)?);
+                }
+                QueryCommands::Graph {
+                    package,
+                    channel,
+                    format,
+                    file,
+                    catalog,
+                    arch,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let dc_map = get_package_config(&configs_dir, package);
+                    if dc_map.is_empty() {
+                        error!("[main] package {} not found in catalog", package);
+                        process::exit(1);
+                    }
+                    let graphs = get_package_graphs(package, channel.as_deref(), &dc_map)?;
+                    let graph = format_graph(package, &graphs, format);
+                    match file {
+                        Some(file) => {
+                            fs::write(file, graph + "\n").map_err(|e| {
+                                MirrorError::new(&format!("writing graph {} {}", file, e))
+                            })?;
+                            info!("[main] graph written to {}", file);
+                        }
+                        None => println!("{}", graph),
+                    }
+                }
+                QueryCommands::Dependencies {
+                    package,
+                    bundle,
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let index = CatalogIndex::load(&configs_dir)?;
+                    let tree = resolve_dependencies(&index, package, bundle)?;
+                    println!("{}", format_dependencies(&tree, output)?);
+                }
+                QueryCommands::Dependents {
+                    package,
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let catalog = CatalogIndex::load(&configs_dir)?;
+                    let index = ReverseIndex::new(&catalog, &GvkIndex::new(&catalog));
+                    let dependents = query_dependents(&catalog, &index, package)?;
+                    println!("{}", format_dependents(&dependents, output)?);
+                }
+                QueryCommands::Provides {
+                    gvk,
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let index = GvkIndex::new(&CatalogIndex::load(&configs_dir)?);
+                    let provided = query_provides(&index, gvk)?;
+                    println!("{}", format_provides(&provided, output)?);
+                }
+                QueryCommands::UpgradePath {
+                    package,
+                    from,
+                    to,
+                    channel,
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let dc_map = get_package_config(&configs_dir, package);
+                    if dc_map.is_empty() {
+                        error!("[main] package {} not found in catalog", package);
+                        process::exit(1);
+                    }
+                    let path = query_upgrade_path(
+                        package,
+                        channel.as_deref(),
+                        from,
+                        to.as_deref(),
+                        &dc_map,
+                    )?;
+                    println!("{}", format_upgrade_path(&path, output)?);
+                }
+            }
+        }
+        Some(Commands::Show { show }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            match show {
+                ShowCommands::Bundle {
+                    package,
+                    bundle,
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (_, configs_dir, _) = select_catalog(&state, catalog, arch);
+                    let dc_map = get_package_config(&configs_dir, package);
+                    if dc_map.is_empty() {
+                        error!("[main] package {} not found in catalog", package);
+                        process::exit(1);
+                    }
+                    let details = get_bundle_details(package, bundle, &dc_map, &configs_dir)?;
+                    println!("{}", format_bundle(&details, output)?);
+                }
+            }
+        }
+        Some(Commands::Snapshots { snapshots }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let mut state = cfg_impl.read_state_or_exit();
+            match snapshots {
+                SnapshotCommands::List {
+                    catalog,
+                    arch,
+                    output,
+                } => {
+                    let (name, _, _) = select_catalog(&state, catalog, arch);
+                    let summaries = query_snapshots(&state, &name)?;
+                    println!("{}", format_snapshots(&summaries, output)?);
+                }
+                SnapshotCommands::Prune {
+                    catalog,
+                    arch,
+                    keep,
+                    max_age,
+                } => {
+                    let (name, _, _) = select_catalog(&state, catalog, arch);
+                    let (key, arch) = name.split_once('/').unwrap_or((&name, "amd64"));
+                    let retention = Retention {
+                        keep: *keep,
+                        max_age_days: *max_age,
+                    };
+                    if let Some(arch_state) = state.get_arch_mut(key, arch) {
+                        let removed =
+                            prune_snapshots(&mut arch_state.snapshots, &retention, Utc::now());
+                        remove_snapshots(&removed);
+                        info!("[main] pruned {} snapshot(s) of {}", removed.len(), name);
+                    }
+                    cfg_impl.write_state(&state)?;
+                }
+            }
+        }
+        Some(Commands::Generate {
+            catalog,
+            arch,
+            package,
+            kind,
+            file,
+        }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let (name, configs_dir, _) = select_catalog(&state, catalog, arch);
+            let mut selection: Vec<PackageFilter> = vec![];
+            for spec in package.iter() {
+                merge_selection(&mut selection, parse_package_spec(spec)?);
+            }
+            check_selection(&configs_dir, &selection)?;
+            let key = name.split('@').next().unwrap_or(&name).to_string();
+            let image = state.images_map().get(&key).cloned().unwrap_or(key);
+            let config = format_mirror_config(kind, &[(image, selection)])?;
+            match file {
+                Some(file) => {
+                    fs::write(file, config)
+                        .map_err(|e| MirrorError::new(&format!("writing config {} {}", file, e)))?;
+                    info!("[main] config written to {}", file);
+                }
+                None => print!("{}", config),
+            }
+        }
+        Some(Commands::Validate {
+            catalog,
+            arch,
+            package,
+            output,
+        }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let (name, configs_dir, _) = select_catalog(&state, catalog, arch);
+            let report = query_validate(&name, &configs_dir, package)?;
+            println!("{}", format_validation(&report, output)?);
+            if report.errors > 0 {
+                process::exit(1);
+            }
+        }
+        Some(Commands::Serve { address }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let address = address
+                .parse::<SocketAddr>()
+                .map_err(|e| MirrorError::new(&format!("invalid address {} {}", address, e)))?;
+            let server_state = ServerState::new(&state);
+            info!("[main] serving {} catalogs", server_state.catalogs.len());
+            let (address, server) = bind(&address, Arc::new(server_state))?;
+            info!("[main] listening on http://{}/api/v1/catalogs", address);
+            server.await?;
+        }
+        Some(Commands::ServeRegistry {
+            catalog,
+            arch,
+            address,
+        }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let (name, configs_dir, _) = select_catalog(&state, catalog, arch);
+            let address = address
+                .parse::<SocketAddr>()
+                .map_err(|e| MirrorError::new(&format!("invalid address {} {}", address, e)))?;
+            let service = RegistryService::new(CatalogIndex::load(&configs_dir)?);
+            let (address, server) = server::grpc::bind(&address, service).await?;
+            info!("[main] serving {} (registry grpc api) on {}", name, address);
+            server
+                .await
+                .map_err(|e| MirrorError::new(&format!("grpc server {}", e)))?;
+        }
+        Some(Commands::Diff {
+            from,
+            to,
+            arch,
+            output,
+        }) => {
+            let cfg_impl = ViewConfig::new(args.state_file.clone());
+            let state = cfg_impl.read_state_or_exit();
+            let (from_name, from_dir, _) = select_catalog(&state, &Some(from.clone()), arch);
+            let (to_name, to_dir, _) = select_catalog(&state, &Some(to.clone()), arch);
+            let diff = query_diff((&from_name, &from_dir), (&to_name, &to_dir))?;
+            println!("{}", format_diff(&diff, output)?);
+        }
         None => {
             error!("[main] sub command not recognized use --help to list commands");
             process::exit(1);
@@ -151,6 +513,49 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     Ok(())
 }
 
+/// select a registered catalog (or catalog@snapshot) for the non interactive subcommands
+// returns the catalog name, configs dir and packages filter, exits on error
+fn select_catalog(
+    state: &ViewState,
+    catalog: &Option<String>,
+    arch: &Option<String>,
+) -> (String, String, Vec<PackageFilter>) {
+    // a local fbc directory is prepared in the cache instead
+    if let Some(dir) = catalog.as_deref().filter(|c| is_fbc_dir(c)) {
+        match open_fbc_dir(dir) {
+            Ok((name, configs_dir)) => return (name, configs_dir, vec![]),
+            Err(err) => {
+                error!("[main] {}", err);
+                process::exit(1);
+            }
+        }
+    }
+    let arch = arch.as_deref().unwrap_or("amd64");
+    let map = state.configs_map();
+    let catalogs = filter_catalogs_by_arch(map.keys().cloned().collect(), arch);
+    // catalog@snapshot selects a snapshot of the catalog
+    let (query, snapshot) = match catalog.as_deref().and_then(|c| c.split_once('@')) {
+        Some((query, snapshot)) => (Some(query), Some(snapshot)),
+        None => (catalog.as_deref(), None),
+    };
+    let res = resolve_catalog(&catalogs, query).and_then(|name| {
+        let filters = state.packages_map().get(&name).cloned().unwrap_or_default();
+        match snapshot {
+            Some(id) => state
+                .find_snapshot(&name, id)
+                .map(|snap| (format!("{}@{}", name, snap.id), snap.configs_dir, filters)),
+            None => Ok((name.clone(), map.get(&name).unwrap().clone(), filters)),
+        }
+    });
+    match res {
+        Ok(selected) => selected,
+        Err(err) => {
+            error!("[main] {}", err);
+            process::exit(1);
+        }
+    }
+}
+
 fn init_error_hooks() -> color_eyre::Result<()> {
     let (panic, error) = HookBuilder::default().into_hooks();
     let panic = panic.into_panic_hook();
@@ -0,0 +1,244 @@
+use mirror_catalog::DeclarativeConfig;
+use semver::Version;
+use serde_derive::{Deserialize, Serialize};
+use std::collections::HashMap;
+use std::fmt;
+
+/// group/version/kind from olm.gvk and olm.gvk.required properties
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Gvk {
+    pub group: String,
+    pub version: String,
+    pub kind: String,
+}
+
+/// package requirement from olm.package.required properties
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct RequiredPackage {
+    pub package_name: String,
+    pub version_range: Option<String>,
+}
+
+impl fmt::Display for Gvk {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}/{}/{}", self.group, self.version, self.kind)
+    }
+}
+
+/// get the values of all properties of the given type (i.e olm.package, olm.gvk)
+// values are returned as json so that every field of the property is available
+pub fn get_properties(dc: &DeclarativeConfig, type_prop: &str) -> Vec<serde_json::Value> {
+    let mut values: Vec<serde_json::Value> = vec![];
+    if let Some(properties) = dc.properties.as_ref() {
+        for item in properties.iter() {
+            if item.type_prop == type_prop {
+                if let Ok(value) = serde_json::to_value(&item.value) {
+                    values.push(value);
+                }
+            }
+        }
+    }
+    values
+}
+
+/// version range as used by skipRange and versionRange i.e ">=4.1.0 <4.2.0 || 5.0.0"
+// comparators separated by spaces are AND'ed, groups separated by || are OR'ed
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionRange {
+    pub groups: Vec<Vec<(String, Version)>>,
+}
+
+impl VersionRange {
+    /// parse the range, returns None if any comparator is not a valid version
+    pub fn parse(range: &str) -> Option<Self> {
+        let mut groups: Vec<Vec<(String, Version)>> = vec![];
+        for group in range.split("||") {
+            let mut comparators: Vec<(String, Version)> = vec![];
+            // allow ">= 1.0.0" by joining a dangling operator with the next token
+            let mut pending = String::new();
+            for token in group.split_whitespace() {
+                let token = format!("{}{}", pending, token);
+                let pos = token
+                    .find(|c: char| !matches!(c, '>' | '<' | '=' | '!'))
+                    .unwrap_or(token.len());
+                if pos == token.len() {
+                    pending = token;
+                    continue;
+                }
+                pending.clear();
+                let (op, version) = token.split_at(pos);
+                let op = match op {
+                    "" | "=" | "==" => "=",
+                    ">" | ">=" | "<" | "<=" | "!=" | "!" => op,
+                    _ => return None,
+                };
+                comparators.push((op.to_string(), parse_version(version)?));
+            }
+            if !pending.is_empty() || comparators.is_empty() {
+                return None;
+            }
+            groups.push(comparators);
+        }
+        Some(Self { groups })
+    }
+
+    /// check if the version is within the range
+    pub fn matches(&self, version: &Version) -> bool {
+        self.groups.iter().any(|group| {
+            group.iter().all(|(op, v)| match op.as_str() {
+                ">" => version > v,
+                ">=" => version >= v,
+                "<" => version < v,
+                "<=" => version <= v,
+                "!=" | "!" => version != v,
+                _ => version == v,
+            })
+        })
+    }
+}
+
+/// get the gvks of the given property type (olm.gvk or olm.gvk.required)
+pub fn get_gvks(dc: &DeclarativeConfig, type_prop: &str) -> Vec<Gvk> {
+    get_properties(dc, type_prop)
+        .into_iter()
+        .filter_map(|v| serde_json::from_value::<Gvk>(v).ok())
+        .collect()
+}
+
+/// get the required packages (olm.package.required)
+pub fn get_required_packages(dc: &DeclarativeConfig) -> Vec<RequiredPackage> {
+    get_properties(dc, "olm.package.required")
+        .into_iter()
+        .filter_map(|v| serde_json::from_value::<RequiredPackage>(v).ok())
+        .collect()
+}
+
+/// parse a version (strips a leading v and pads a missing minor or patch)
+pub fn parse_version(value: &str) -> Option<Version> {
+    let value = value.trim().trim_start_matches('v');
+    if let Ok(version) = Version::parse(value) {
+        return Some(version);
+    }
+    // split off pre-release/build metadata before padding
+    let pos = value.find(['-', '+']).unwrap_or(value.len());
+    let (core, rest) = value.split_at(pos);
+    let parts = core.split('.').collect::<Vec<&str>>();
+    if parts.is_empty() || parts.len() > 3 || parts.iter().any(|p| p.parse::<u64>().is_err()) {
+        return None;
+    }
+    let mut padded = parts.join(".");
+    for _ in parts.len()..3 {
+        padded.push_str(".0");
+    }
+    Version::parse(&format!("{}{}", padded, rest)).ok()
+}
+
+/// get the version encoded in a bundle name i.e 3scale-operator.v0.10.0
+pub fn version_from_name(name: &str) -> Option<Version> {
+    for (pos, _) in name.match_indices('.') {
+        if let Some(version) = parse_version(&name[pos + 1..]) {
+            return Some(version);
+        }
+    }
+    None
+}
+
+/// get the version of a bundle from its olm.package property
+// falls back to the version encoded in the bundle name
+pub fn get_bundle_version(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    bundle: &str,
+) -> Option<Version> {
+    if let Some(dc) = dc_map.get(&format!("{}=olm.bundle", bundle)) {
+        for value in get_properties(dc, "olm.package").iter() {
+            if let Some(version) = value.get("version").and_then(|v| v.as_str()) {
+                if let Some(version) = parse_version(version) {
+                    return Some(version);
+                }
+            }
+        }
+    }
+    version_from_name(bundle)
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn parse_version_pass() {
+        assert_eq!(parse_version("v1.2.3"), Some(Version::new(1, 2, 3)));
+        assert_eq!(parse_version("4.16"), Some(Version::new(4, 16, 0)));
+        assert_eq!(parse_version("4.16.0-rhodf").unwrap().pre.as_str(), "rhodf");
+        assert_eq!(
+            parse_version("4.16-202401"),
+            Version::parse("4.16.0-202401").ok()
+        );
+        assert_eq!(parse_version("stable"), None);
+    }
+
+    #[test]
+    fn get_gvks_pass() {
+        let dc_map = crate::operator::catalog::declarative_config_from_file(
+            "test-artifacts/declarative-config/albo-operator.json",
+        );
+        let dc = dc_map.get("albo-operator.v1.2.0=olm.bundle").unwrap();
+        let provided = get_gvks(dc, "olm.gvk");
+        assert_eq!(provided.len(), 2);
+        assert_eq!(
+            provided[0].to_string(),
+            "networking.example.com/v1/TargetGroupBinding"
+        );
+        let required = get_gvks(dc, "olm.gvk.required");
+        assert_eq!(required[0].kind, "ServiceMonitor");
+        assert_eq!(
+            get_required_packages(dc),
+            vec![RequiredPackage {
+                package_name: "cert-manager-operator".to_string(),
+                version_range: Some(">=1.12.0 <2.0.0".to_string()),
+            }]
+        );
+        assert_eq!(
+            get_bundle_version(&dc_map, "albo-operator.v1.2.0"),
+            Some(Version::new(1, 2, 0))
+        );
+    }
+
+    #[test]
+    fn version_range_pass() {
+        let range = VersionRange::parse(">=4.1.0 <4.2.0").unwrap();
+        assert!(range.matches(&Version::new(4, 1, 5)));
+        assert!(!range.matches(&Version::new(4, 2, 0)));
+        let range = VersionRange::parse(">= 1.0.0 <1.2.0 || 2.0.0").unwrap();
+        assert!(range.matches(&Version::new(1, 1, 0)));
+        assert!(range.matches(&Version::new(2, 0, 0)));
+        assert!(!range.matches(&Version::new(2, 0, 1)));
+        let range = VersionRange::parse(">=4.16.0-0 <4.16.3").unwrap();
+        assert!(range.matches(&Version::parse("4.16.2-rhodf").unwrap()));
+        assert!(VersionRange::parse(">=4.x").is_none());
+        assert!(VersionRange::parse("").is_none());
+        assert!(VersionRange::parse(">=").is_none());
+    }
+
+    #[test]
+    fn version_from_name_pass() {
+        assert_eq!(
+            version_from_name("3scale-operator.v0.10.0"),
+            Some(Version::new(0, 10, 0))
+        );
+        assert_eq!(
+            version_from_name("odf-operator.v4.16.0-rhodf")
+                .unwrap()
+                .pre
+                .as_str(),
+            "rhodf"
+        );
+        assert_eq!(
+            version_from_name("elasticsearch-operator.5.8.1"),
+            Some(Version::new(5, 8, 1))
+        );
+        assert_eq!(version_from_name("no-version"), None);
+    }
+}
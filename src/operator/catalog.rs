@@ -0,0 +1,252 @@
+use crate::operator::bundle::get_bundle_version;
+use mirror_catalog::{ChannelEntry, DeclarativeConfig};
+use mirror_error::MirrorError;
+use serde::Deserialize;
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::{Path, PathBuf};
+
+/// load the declarative config map of a package (keyed by name=schema)
+// configs_dir is the path registered in the state file (with trailing '/')
+pub fn get_package_config(configs_dir: &str, pkg: &str) -> HashMap<String, DeclarativeConfig> {
+    DeclarativeConfig::get_declarativeconfig_map(format!("{}{}/updated-configs/", configs_dir, pkg))
+}
+
+/// get the default channel from the olm.package entry
+pub fn get_default_channel(dc_map: &HashMap<String, DeclarativeConfig>) -> Option<String> {
+    dc_map
+        .iter()
+        .find(|(k, _)| k.ends_with("=olm.package"))
+        .and_then(|(_, v)| v.default_channel.clone())
+}
+
+/// get the channels (name -> entries)
+pub fn get_channels(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+) -> BTreeMap<String, Vec<ChannelEntry>> {
+    let mut channels: BTreeMap<String, Vec<ChannelEntry>> = BTreeMap::new();
+    for (k, v) in dc_map.iter() {
+        if let Some(name) = k.strip_suffix("=olm.channel") {
+            channels.insert(name.to_string(), v.entries.clone().unwrap_or_default());
+        }
+    }
+    channels
+}
+
+/// get the bundles (name -> declarative config)
+pub fn get_bundles(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+) -> BTreeMap<String, DeclarativeConfig> {
+    let mut bundles: BTreeMap<String, DeclarativeConfig> = BTreeMap::new();
+    for (k, v) in dc_map.iter() {
+        if let Some(name) = k.strip_suffix("=olm.bundle") {
+            bundles.insert(name.to_string(), v.clone());
+        }
+    }
+    bundles
+}
+
+/// get the head of a channel
+// the head is the entry that is not replaced or skipped by any other entry,
+// if there are more than one the highest version wins
+pub fn get_channel_head(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    entries: &[ChannelEntry],
+) -> Option<String> {
+    let mut heads = entries
+        .iter()
+        .filter(|e| {
+            !entries.iter().any(|other| {
+                other.replaces.as_deref() == Some(e.name.as_str())
+                    || other
+                        .skips
+                        .as_ref()
+                        .map(|s| s.contains(&e.name))
+                        .unwrap_or(false)
+            })
+        })
+        .map(|e| e.name.clone())
+        .collect::<Vec<String>>();
+    heads.sort_by_key(|name| get_bundle_version(dc_map, name));
+    heads.pop()
+}
+
+/// parse the fbc objects of a file (json stream or array, or multi document yaml)
+pub fn parse_fbc(contents: &str, yaml: bool) -> Result<Vec<DeclarativeConfig>, MirrorError> {
+    let mut configs: Vec<DeclarativeConfig> = vec![];
+    for value in parse_fbc_values(contents, yaml)?.into_iter() {
+        configs.push(
+            serde_json::from_value(value)
+                .map_err(|e| MirrorError::new(&format!("parsing fbc object {}", e)))?,
+        );
+    }
+    Ok(configs)
+}
+
+/// parse the objects of a file as json values (every field is kept)
+pub fn parse_fbc_values(contents: &str, yaml: bool) -> Result<Vec<serde_json::Value>, MirrorError> {
+    let mut values: Vec<serde_json::Value> = vec![];
+    let mut push = |value: serde_json::Value| match value {
+        serde_json::Value::Array(array) => values.extend(array),
+        serde_json::Value::Null => {}
+        value => values.push(value),
+    };
+    if yaml {
+        for doc in serde_yaml::Deserializer::from_str(contents) {
+            let value = serde_json::Value::deserialize(doc)
+                .map_err(|e| MirrorError::new(&format!("parsing fbc yaml {}", e)))?;
+            push(value);
+        }
+    } else {
+        for value in serde_json::Deserializer::from_str(contents).into_iter::<serde_json::Value>() {
+            push(value.map_err(|e| MirrorError::new(&format!("parsing fbc json {}", e)))?);
+        }
+    }
+    Ok(values)
+}
+
+/// load the fbc objects of a package as they are in the catalog (duplicates are kept)
+// reads the json and yaml files of <configs_dir>/<pkg>/ (updated-configs is skipped)
+pub fn load_package_fbc(
+    configs_dir: &str,
+    pkg: &str,
+) -> Result<Vec<DeclarativeConfig>, MirrorError> {
+    let mut configs: Vec<DeclarativeConfig> = vec![];
+    for (file, yaml) in package_files(configs_dir, pkg)?.iter() {
+        let contents = fs::read_to_string(file)
+            .map_err(|e| MirrorError::new(&format!("reading file {:?} {}", file, e)))?;
+        configs.extend(
+            parse_fbc(&contents, *yaml)
+                .map_err(|e| MirrorError::new(&format!("{:?} {}", file, e)))?,
+        );
+    }
+    Ok(configs)
+}
+
+/// get the olm.bundle object of a bundle as it is in the catalog (every field is kept)
+// returns None when the files of the package can't be read
+pub fn load_bundle_value(configs_dir: &str, pkg: &str, bundle: &str) -> Option<serde_json::Value> {
+    for (file, yaml) in package_files(configs_dir, pkg).ok()?.iter() {
+        let contents = fs::read_to_string(file).ok()?;
+        let value = parse_fbc_values(&contents, *yaml)
+            .ok()?
+            .into_iter()
+            .find(|value| value["schema"] == "olm.bundle" && value["name"] == bundle);
+        if value.is_some() {
+            return value;
+        }
+    }
+    None
+}
+
+/// list the json and yaml files (with the yaml flag) of <configs_dir>/<pkg>/
+fn package_files(configs_dir: &str, pkg: &str) -> Result<Vec<(PathBuf, bool)>, MirrorError> {
+    let dir = Path::new(configs_dir).join(pkg);
+    let mut files = fs::read_dir(&dir)
+        .map_err(|e| MirrorError::new(&format!("reading directory {:?} {}", dir, e)))?
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file())
+        .filter_map(|p| match p.extension().and_then(|e| e.to_str()) {
+            Some("json") => Some((p, false)),
+            Some("yaml") | Some("yml") => Some((p, true)),
+            _ => None,
+        })
+        .collect::<Vec<_>>();
+    files.sort();
+    Ok(files)
+}
+
+/// build a declarative config map from a json array of fbc objects
+#[cfg(test)]
+pub fn declarative_config_from_file(path: &str) -> HashMap<String, DeclarativeConfig> {
+    let contents = std::fs::read_to_string(path).expect("should read declarative config file");
+    let configs: Vec<DeclarativeConfig> =
+        serde_json::from_str(&contents).expect("should parse declarative config file");
+    configs
+        .into_iter()
+        .map(|dc| (format!("{}={}", dc.name, dc.schema), dc))
+        .collect()
+}
+
+/// build a catalog index from the test-artifacts/declarative-config files of the packages
+#[cfg(test)]
+pub fn catalog_index_from_files(pkgs: &[&str]) -> crate::operator::dependency::CatalogIndex {
+    crate::operator::dependency::CatalogIndex {
+        packages: pkgs
+            .iter()
+            .map(|pkg| {
+                (
+                    pkg.to_string(),
+                    declarative_config_from_file(&format!(
+                        "test-artifacts/declarative-config/{}.json",
+                        pkg
+                    )),
+                )
+            })
+            .collect(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn get_channels_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        assert_eq!(get_default_channel(&dc_map), Some("stable-v1".to_string()));
+        let channels = get_channels(&dc_map);
+        assert_eq!(
+            channels.keys().cloned().collect::<Vec<String>>(),
+            vec!["stable-v0".to_string(), "stable-v1".to_string()]
+        );
+        assert_eq!(get_bundles(&dc_map).len(), 5);
+    }
+
+    #[test]
+    fn parse_fbc_pass() {
+        let json = r#"{"schema": "olm.package", "name": "albo-operator", "defaultChannel": "stable-v1"}
+{"schema": "olm.channel", "name": "stable-v1", "package": "albo-operator", "entries": [{"name": "albo-operator.v1.0.0"}]}"#;
+        let res = parse_fbc(json, false).unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[1].entries.as_ref().unwrap()[0].name,
+            "albo-operator.v1.0.0"
+        );
+        let yaml = "---
+schema: olm.package
+name: albo-operator
+---
+schema: olm.bundle
+name: albo-operator.v1.0.0
+package: albo-operator
+image: registry.example.com/albo/albo-operator-bundle@sha256:1000
+";
+        let res = parse_fbc(yaml, true).unwrap();
+        assert_eq!(
+            res[1].image.as_deref(),
+            Some("registry.example.com/albo/albo-operator-bundle@sha256:1000")
+        );
+        let contents =
+            fs::read_to_string("test-artifacts/declarative-config/albo-operator.json").unwrap();
+        assert_eq!(parse_fbc(&contents, false).unwrap().len(), 8);
+        assert!(parse_fbc("{\"name\": ", false).is_err());
+    }
+
+    #[test]
+    fn get_channel_head_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        assert_eq!(
+            get_channel_head(&dc_map, channels.get("stable-v1").unwrap()),
+            Some("albo-operator.v1.2.0".to_string())
+        );
+        assert_eq!(
+            get_channel_head(&dc_map, channels.get("stable-v0").unwrap()),
+            Some("albo-operator.v1.0.0".to_string())
+        );
+    }
+}
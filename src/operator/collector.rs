@@ -1,5 +1,10 @@
+use crate::batch::blob::{blob_client, blob_store_path, download_blob, BlobInterface};
 use crate::batch::worker::execute_batch;
-use crate::cli::config::ViewConfig;
+use crate::cli::config::{get_digest, ViewConfig, ViewState};
+use crate::operator::filter::PackageFilter;
+use crate::operator::snapshot::*;
+use crate::operator::sqlite::migrate_sqlite_cache;
+use chrono::Utc;
 use custom_logger::*;
 use mirror_auth::*;
 use mirror_catalog::*;
@@ -12,17 +17,20 @@ use mirror_utils::{
     process_and_update_manifest, FsLayer,
 };
 use std::collections::HashMap;
+use std::fs;
 use std::fs::DirBuilder;
 use std::os::unix::fs::DirBuilderExt;
 use std::path::Path;
 
 // download the latest catalog
-pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
+pub async fn get_operator_catalog<T: DownloadImageInterface + BlobInterface + Clone>(
     reg_con: T,
     dir: String,
-    _all_arch: bool,
+    all_arch: bool,
     token_enable: bool,
     operators: Vec<Operator>,
+    cfg_impl: ViewConfig,
+    retention: Retention,
 ) -> Result<(), MirrorError> {
     info!("[get_operator_catalog] collector");
     // set up dir to store all manifests
@@ -33,8 +41,8 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
     )
     .await?;
 
-    let cfg_impl = ViewConfig::new();
-    let mut map_config: HashMap<String, String> = cfg_impl.read_config();
+    let mut state = cfg_impl.read_state()?;
+    let client = blob_client(true)?;
 
     // parse the config - iterate through each catalog
     for operator in operators.clone().iter() {
@@ -42,6 +50,10 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
         debug!("image refs {:#?}", ir.clone());
 
         let blobs_dir = dir.clone() + "/blobs-store";
+        let blobs_url = format!(
+            "https://{}/v2/{}/{}/blobs/",
+            ir.registry, ir.namespace, ir.name
+        );
         let manifestlist: String;
         let t_impl = ImplTokenInterface {};
 
@@ -106,10 +118,82 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
         } else {
             manifestlist = res.clone();
         }
-        let local_manifestlist = manifestlist.clone();
-        let local_pml = parse_json_manifestlist(local_manifestlist.clone())?;
-        for m in local_pml.clone().manifests.iter() {
-            let arch = m.platform.as_ref().unwrap().architecture.to_string();
+        let catalog_key = format!("{}:{}", ir.name, ir.version);
+        let catalog_digest = get_digest(manifestlist.clone());
+        // collect (architecture, manifest) for each image to process
+        let mut arch_manifests: Vec<(String, String)> = vec![];
+        if is_manifest_list(manifestlist.clone()) {
+            let local_pml = parse_json_manifestlist(manifestlist.clone())?;
+            let manifest_url_of = |digest: &str| {
+                format!(
+                    "https://{}/v2/{}/{}/manifests/{}",
+                    ir.registry, ir.namespace, ir.name, digest
+                )
+            };
+            // collect (architecture, digest, manifest if already fetched) for each image
+            let mut images: Vec<(String, String, Option<String>)> = vec![];
+            for m in local_pml.manifests.iter() {
+                let digest = m.digest.clone().unwrap_or_default();
+                match m.platform.as_ref().map(|p| p.architecture.to_string()) {
+                    // attestation manifests (buildkit) use unknown/unknown as platform
+                    Some(arch) if arch == "unknown" => continue,
+                    Some(arch) => images.push((arch, digest, None)),
+                    None => {
+                        let manifest = reg_con
+                            .get_manifest(manifest_url_of(&digest), token.clone())
+                            .await?;
+                        let arch = get_config_arch(
+                            &reg_con, &client, &blobs_dir, &blobs_url, &token, &manifest,
+                        )
+                        .await?;
+                        images.push((arch, digest, Some(manifest)));
+                    }
+                }
+            }
+            let archs = images
+                .iter()
+                .map(|(arch, _, _)| arch.clone())
+                .collect::<Vec<String>>();
+            // as all architecture index files are usually identical
+            // it's ok to get one architecture as reference unless all-arch is set
+            let reference_arch = if archs.contains(&"amd64".to_string()) {
+                "amd64".to_string()
+            } else {
+                archs.first().cloned().unwrap_or_default()
+            };
+            for (arch, digest, manifest) in images.into_iter() {
+                if !all_arch && arch != reference_arch {
+                    continue;
+                }
+                let manifest = match manifest {
+                    Some(manifest) => manifest,
+                    None => {
+                        reg_con
+                            .get_manifest(manifest_url_of(&digest), token.clone())
+                            .await?
+                    }
+                };
+                arch_manifests.push((arch, manifest));
+            }
+        } else {
+            // single manifest (no manifest list) image
+            let arch = get_config_arch(
+                &reg_con,
+                &client,
+                &blobs_dir,
+                &blobs_url,
+                &token,
+                &manifestlist,
+            )
+            .await?;
+            info!(
+                "[get_operator_catalog] single manifest image with architecture {}",
+                arch
+            );
+            arch_manifests.push((arch, manifestlist.clone()));
+        }
+
+        for (arch, manifest) in arch_manifests.iter() {
             let manifest_json = format!(
                 "{}/{}/{}/{}/manifest.json",
                 dir.clone(),
@@ -122,16 +206,6 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
             let manifest_dir = manifest_json.split("manifest.json").nth(0).unwrap();
             info!("[get_operator_catalog] manifest directory {}", manifest_dir);
             fs_handler(manifest_dir.to_string(), "create_dir", None).await?;
-            let mnfst_url = &format!(
-                "https://{}/v2/{}/{}/manifests/{}",
-                ir.registry,
-                ir.namespace,
-                ir.name,
-                m.digest.as_ref().unwrap()
-            );
-            let manifest = reg_con
-                .get_manifest(mnfst_url.clone(), token.clone())
-                .await?;
             let working_dir_cache = format!(
                 "{}/{}/{}/{}/cache",
                 dir.clone(),
@@ -139,7 +213,6 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
                 ir.version.clone(),
                 arch.clone(),
             );
-            let cache_exists = Path::new(&working_dir_cache).exists();
             debug!(
                 "[get_operator_catalog] main operator manifest file {}",
                 manifest_json
@@ -150,22 +223,19 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
                 HashMap::new(),
             )
             .await?;
-            if changed.is_some() {
+            let is_changed = changed.is_some();
+            if is_changed {
                 info!("[get_operator_catalog] detected change in manifest");
                 let changed_manifest = fs_handler(changed.unwrap().clone(), "read", None).await?;
                 let res_pm = parse_json_manifest_operator(changed_manifest.clone())?;
 
-                if cache_exists {
-                    // detected a change so clean the dir contents
-                    rm_rf::remove(&working_dir_cache)
-                        .expect("[get_operator_catalog] should delete current untarred cache");
-                }
-                // re-create the cache directory
-                let mut builder = DirBuilder::new();
-                builder.mode(0o777);
-                builder
-                    .create(&working_dir_cache)
-                    .expect("[get_operator_catalog] unable to create directory");
+                reset_cache(
+                    &mut state,
+                    &catalog_key,
+                    arch,
+                    &working_dir_cache,
+                    &retention,
+                );
 
                 let mut fslayers: Vec<FsLayer> = vec![];
                 for l in res_pm.clone().layers.unwrap().iter() {
@@ -176,14 +246,10 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
                     };
                     fslayers.insert(0, fsl);
                 }
-                let blobs_url = format!(
-                    "https://{}/v2/{}/{}/blobs/",
-                    ir.registry, ir.namespace, ir.name
-                );
                 let mut hm: HashMap<String, Vec<FsLayer>> = HashMap::new();
-                hm.insert(blobs_url, fslayers.clone());
+                hm.insert(blobs_url.clone(), fslayers.clone());
                 // use a concurrent process to get related blobs
-                execute_batch(reg_con.clone(), blobs_dir.clone(), false, true, hm).await?;
+                execute_batch(reg_con.clone(), blobs_dir.clone(), true, true, hm).await?;
                 debug!("[get_operator_catalog] completed image index download");
                 debug!("[get_operator_catalog] map {:#?}", fslayers.clone(),);
                 untar_layers(
@@ -194,43 +260,216 @@ pub async fn get_operator_catalog<T: DownloadImageInterface + Clone>(
                 .await;
 
                 info!("[get_operator_catalog] completed untar of layers");
-                // find the directory 'configs'
-                let config_dir = find_dir(working_dir_cache.clone(), "configs".to_string()).await;
-                if config_dir.len() == 0 {
-                    warn!("[get_operator_catalog] 'configs' directory is empty");
-                } else {
-                    // add to the array
-                    let vec_full_path = config_dir.split("/").collect::<Vec<&str>>();
-                    let pos = vec_full_path.iter().position(|x| x == &"amd64").unwrap();
-                    let name = format!("{}:{}", vec_full_path[pos - 2], vec_full_path[pos - 1]);
-                    map_config.insert(name.clone(), format!("{}/", config_dir.clone()));
-
-                    info!(
-                        "[get_operator_catalog] full path for directory 'configs' \x1b[1;94m{}\x1b[0m/ ",
-                        &config_dir
-                    );
-                    DeclarativeConfig::build_updated_configs(config_dir.clone())
-                        .expect("[get_operator_catalog] should build updated configs");
-                }
             }
 
-            // as all architecture index files are identical
-            // it's ok to get one architecture as reference
-            if arch.clone() == "amd64" {
-                break;
+            register_cache(
+                &mut state,
+                &catalog_key,
+                &operator.catalog,
+                &catalog_digest,
+                arch,
+                &get_digest(manifest.clone()),
+                &working_dir_cache,
+                is_changed,
+                &retention,
+            )
+            .await;
+        }
+
+        // record the packages filter so that the viewer can honor it
+        let filters = operator
+            .packages
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(PackageFilter::from_config)
+            .collect::<Vec<PackageFilter>>();
+        state.set_packages(&catalog_key, filters.clone());
+        if let Some(catalog) = state.catalogs.get(&catalog_key) {
+            if let Some(arch_state) = catalog.architectures.values().next() {
+                let packages =
+                    DeclarativeConfig::get_packages(&arch_state.configs_dir).unwrap_or_default();
+                for f in filters.iter() {
+                    if !packages.contains(&f.name) {
+                        warn!(
+                            "[get_operator_catalog] package {} not found in catalog {}",
+                            f.name, operator.catalog
+                        );
+                    }
+                }
             }
         }
     }
-    cfg_impl.write_config(map_config);
+    cfg_impl.write_state(&state)?;
     Ok(())
 }
 
+/// keep the current cache as a snapshot (or remove it when the history is disabled)
+// and re-create an empty cache directory for the new layers
+pub fn reset_cache(
+    state: &mut ViewState,
+    catalog_key: &str,
+    arch: &str,
+    working_dir_cache: &str,
+    retention: &Retention,
+) {
+    if Path::new(working_dir_cache).exists() {
+        let snapshot = match state.get_arch(catalog_key, arch) {
+            Some(current) if retention.keep > 0 => archive_cache(working_dir_cache, current)
+                .map_err(|err| {
+                    warn!("[reset_cache] unable to archive cache {}", err);
+                }),
+            _ => Err(()),
+        };
+        match snapshot {
+            Ok(snapshot) => {
+                info!(
+                    "[reset_cache] archived previous contents as snapshot {}",
+                    snapshot.id
+                );
+                state.add_snapshot(catalog_key, arch, snapshot);
+            }
+            Err(_) => {
+                rm_rf::remove(working_dir_cache)
+                    .expect("[reset_cache] should delete current untarred cache");
+            }
+        }
+    }
+    let mut builder = DirBuilder::new();
+    builder.mode(0o777);
+    builder
+        .create(working_dir_cache)
+        .expect("[reset_cache] unable to create directory");
+}
+
+/// register the 'configs' directory of an untarred cache (if it changed or is missing from the state file)
+// builds the updated configs when the cache changed and applies the snapshot retention settings
+#[allow(clippy::too_many_arguments)]
+pub async fn register_cache(
+    state: &mut ViewState,
+    catalog_key: &str,
+    image: &str,
+    catalog_digest: &str,
+    arch: &str,
+    arch_digest: &str,
+    working_dir_cache: &str,
+    is_changed: bool,
+    retention: &Retention,
+) {
+    let registered = state
+        .catalogs
+        .get(catalog_key)
+        .map(|c| c.architectures.contains_key(arch))
+        .unwrap_or(false);
+    if (is_changed || !registered) && Path::new(working_dir_cache).exists() {
+        // find the directory 'configs'
+        let mut config_dir = find_dir(working_dir_cache.to_string(), "configs".to_string()).await;
+        // legacy (4.10 and before) catalogs only have a sqlite database, the configs are converted
+        // and the updated configs are built by the migration
+        let mut is_migrated = false;
+        if config_dir.is_empty() {
+            match migrate_sqlite_cache(working_dir_cache, is_changed) {
+                Ok(Some(migrated_dir)) => {
+                    config_dir = migrated_dir;
+                    is_migrated = true;
+                }
+                Ok(None) => {}
+                Err(err) => warn!("[register_cache] unable to convert index.db {}", err),
+            }
+        }
+        if config_dir.is_empty() {
+            warn!("[register_cache] 'configs' directory is empty");
+        } else {
+            // store the absolute path so the viewer can be launched from any directory
+            let configs_dir = fs::canonicalize(&config_dir)
+                .map(|p| p.to_string_lossy().to_string())
+                .unwrap_or(config_dir.clone());
+            state.update_catalog(
+                catalog_key.to_string(),
+                image.to_string(),
+                Some(catalog_digest.to_string()),
+                arch.to_string(),
+                Some(arch_digest.to_string()),
+                format!("{}/", configs_dir),
+            );
+
+            info!(
+                "[register_cache] full path for directory 'configs' \x1b[1;94m{}\x1b[0m/ ",
+                &configs_dir
+            );
+            if is_changed && !is_migrated {
+                DeclarativeConfig::build_updated_configs(config_dir.clone())
+                    .expect("[register_cache] should build updated configs");
+            }
+        }
+    }
+
+    // apply the snapshot retention settings
+    if let Some(arch_state) = state.get_arch_mut(catalog_key, arch) {
+        let removed = prune_snapshots(&mut arch_state.snapshots, retention, Utc::now());
+        remove_snapshots(&removed);
+    }
+}
+
+/// check if the manifest is a manifest list (or oci image index)
+pub fn is_manifest_list(manifest: String) -> bool {
+    let res = serde_json::from_str::<serde_json::Value>(&manifest);
+    match res {
+        Ok(value) => value.get("manifests").is_some(),
+        Err(_) => false,
+    }
+}
+
+/// get the architecture of a single manifest
+// only schema v1 manifests carry the architecture field, default to amd64
+pub fn get_manifest_arch(manifest: String) -> String {
+    let res = serde_json::from_str::<serde_json::Value>(&manifest);
+    if let Ok(value) = res {
+        if let Some(arch) = value.get("architecture").and_then(|a| a.as_str()) {
+            return arch.to_string();
+        }
+    }
+    warn!("[get_manifest_arch] architecture not found in manifest, using amd64");
+    "amd64".to_string()
+}
+
+/// get the architecture of an image from its config blob (manifest list entries without platform)
+// the config is downloaded to the blobs-store, manifests without config (schema v1) use
+// get_manifest_arch
+pub async fn get_config_arch<T: BlobInterface>(
+    blob_impl: &T,
+    client: &reqwest::Client,
+    blobs_dir: &str,
+    blobs_url: &str,
+    token: &str,
+    manifest: &str,
+) -> Result<String, MirrorError> {
+    let value = serde_json::from_str::<serde_json::Value>(manifest).unwrap_or_default();
+    match value["config"]["digest"].as_str() {
+        Some(config) => {
+            download_blob(blob_impl, client, blobs_dir, blobs_url, token, true, config).await?;
+            let path = blob_store_path(blobs_dir, config)?;
+            let contents = fs::read_to_string(&path)
+                .map_err(|e| MirrorError::new(&format!("reading config {:?} {}", path, e)))?;
+            Ok(get_manifest_arch(contents))
+        }
+        None => Ok(get_manifest_arch(manifest.to_string())),
+    }
+}
+
 #[cfg(test)]
 mod tests {
     // this brings everything from parent's scope into this scope
     use super::*;
     use async_trait::async_trait;
+    use mirror_copy::ImplDownloadImageInterface;
+    use sha2::{Digest, Sha256};
     use std::fs;
+    use std::path::PathBuf;
+
+    fn get_digest_bytes(contents: &[u8]) -> String {
+        format!("sha256:{}", hex::encode(Sha256::digest(contents)))
+    }
 
     macro_rules! aw {
         ($e:expr) => {
@@ -238,8 +477,133 @@ mod tests {
         };
     }
 
+    #[derive(Clone)]
+    struct Fake {
+        manifest: String,
+        // blobs served by digest (catalog layer and config)
+        blobs: HashMap<String, Vec<u8>>,
+    }
+
+    #[async_trait]
+    impl DownloadImageInterface for Fake {
+        async fn get_manifest(&self, url: String, _token: String) -> Result<String, MirrorError> {
+            let mut content = String::from("");
+
+            if url.contains("test-index-operator") {
+                content = self.manifest.clone();
+            }
+            if url.contains("cad8f6380b4dd4e1396dafcd7dfbf0f405aa10e4ae36214f849e6a77e6210d92") {
+                content = fs::read_to_string("test-artifacts/simulate-api-call/manifest-list.json")
+                    .expect("should read test (albo) controller manifest-list file");
+            }
+            if url.contains("75012e910726992f70c892b11e50e409852501c64903fa05fa68d89172546d5d")
+                | url.contains("5e03f571c5993f0853a910b7c0cab44ec0e451b94a9677ed82e921b54a4b735a")
+            {
+                content =
+                    fs::read_to_string("test-artifacts/simulate-api-call/manifest-amd64.json")
+                        .expect("should read test (albo) controller manifest-am64 file");
+            }
+            if url.contains("d4d65d0d7c249d076da74da22296280ddef534da2bf54efb9e46d2bd7b9a602d") {
+                content = fs::read_to_string("test-artifacts/simulate-api-call/manifest.json")
+                    .expect("should read test (albo) bundle manifest file");
+            }
+            if url.contains("cbb31de2108b57172409cede667fa24d68d635ac3cc6db4af6e9b6f9dd1c5cd0") {
+                content = fs::read_to_string(
+                    "test-artifacts/simulate-api-call/manifest-amd64-operator.json",
+                )
+                .expect("should read test (albo) operator manifest file");
+            }
+            if url.contains("422e4fbe1ed81c79084f43a826dc0674510a7ff578e62b4ddda119ed3266d0b6") {
+                content =
+                    fs::read_to_string("test-artifacts/simulate-api-call/manifest-amd64-kube.json")
+                        .expect("should read test (openshift) kube-proxy manifest file");
+            }
+
+            Ok(content)
+        }
+
+        async fn get_blob(
+            &self,
+            _dir: String,
+            _url: String,
+            _token: String,
+            _verify_blob: bool,
+            blob_sum: String,
+        ) -> Result<(), MirrorError> {
+            Err(MirrorError::new(&format!(
+                "{} should be downloaded with get_blob_range",
+                blob_sum
+            )))
+        }
+    }
+
+    #[async_trait]
+    impl BlobInterface for Fake {
+        async fn get_blob_range(
+            &self,
+            _client: &reqwest::Client,
+            url: String,
+            _token: String,
+            _offset: u64,
+            file: PathBuf,
+        ) -> Result<(), MirrorError> {
+            let blob = self
+                .blobs
+                .iter()
+                .find(|(digest, _)| url.ends_with(digest.as_str()))
+                .map(|(_, blob)| blob)
+                .ok_or(MirrorError::new(&format!("blob {} not found", url)))?;
+            fs::write(&file, blob)
+                .map_err(|e| MirrorError::new(&format!("writing file {:?} {}", file, e)))
+        }
+    }
+
+    // catalog image (single manifest) with an albo-operator catalog layer and the given config
+    fn catalog_fake(config: &str) -> Fake {
+        // catalog layer (configs/albo-operator/catalog.json) served by the blob mock
+        let mut builder = tar::Builder::new(flate2::write::GzEncoder::new(
+            Vec::new(),
+            flate2::Compression::default(),
+        ));
+        let catalog = fs::read("test-artifacts/fbc-configs/albo-operator/catalog.json")
+            .expect("should read albo catalog file");
+        let mut header = tar::Header::new_gnu();
+        header.set_size(catalog.len() as u64);
+        header.set_mode(0o644);
+        builder
+            .append_data(
+                &mut header,
+                "configs/albo-operator/catalog.json",
+                catalog.as_slice(),
+            )
+            .unwrap();
+        let layer = builder.into_inner().unwrap().finish().unwrap();
+        let layer_digest = get_digest_bytes(&layer);
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "mediaType": "application/vnd.oci.image.manifest.v1+json",
+            "config": {
+                "mediaType": "application/vnd.oci.image.config.v1+json",
+                "digest": get_digest(config.to_string()),
+                "size": config.len()
+            },
+            "layers": [{
+                "mediaType": "application/vnd.oci.image.layer.v1.tar+gzip",
+                "digest": layer_digest,
+                "size": layer.len()
+            }]
+        })
+        .to_string();
+        let mut blobs = HashMap::new();
+        blobs.insert(get_digest(config.to_string()), config.as_bytes().to_vec());
+        blobs.insert(layer_digest, layer);
+        Fake { manifest, blobs }
+    }
+
     #[test]
     fn get_operator_catalog_pass() {
+        let base = "test-artifacts/collector-test";
+        fs::create_dir_all(base).expect("should create collector test folder");
         // we set up a mock server for the auth-credentials
         let mut server = mockito::Server::new();
         let url = server.url();
@@ -264,84 +628,114 @@ mod tests {
             packages: None,
         };
 
-        #[derive(Clone)]
-        struct Fake {}
-
-        #[async_trait]
-        impl DownloadImageInterface for Fake {
-            async fn get_manifest(
-                &self,
-                url: String,
-                _token: String,
-            ) -> Result<String, MirrorError> {
-                let mut content = String::from("");
-
-                if url.contains("test-index-operator") {
-                    content =
-                        fs::read_to_string("test-artifacts/test-index-operator/v1.0/manifest.json")
-                            .expect("should read operator-index manifest file")
-                }
-                if url.contains("cad8f6380b4dd4e1396dafcd7dfbf0f405aa10e4ae36214f849e6a77e6210d92")
-                {
-                    content =
-                        fs::read_to_string("test-artifacts/simulate-api-call/manifest-list.json")
-                            .expect("should read test (albo) controller manifest-list file");
-                }
-                if url.contains("75012e910726992f70c892b11e50e409852501c64903fa05fa68d89172546d5d")
-                    | url.contains(
-                        "5e03f571c5993f0853a910b7c0cab44ec0e451b94a9677ed82e921b54a4b735a",
-                    )
-                {
-                    content =
-                        fs::read_to_string("test-artifacts/simulate-api-call/manifest-amd64.json")
-                            .expect("should read test (albo) controller manifest-am64 file");
-                }
-                if url.contains("d4d65d0d7c249d076da74da22296280ddef534da2bf54efb9e46d2bd7b9a602d")
-                {
-                    content = fs::read_to_string("test-artifacts/simulate-api-call/manifest.json")
-                        .expect("should read test (albo) bundle manifest file");
-                }
-                if url.contains("cbb31de2108b57172409cede667fa24d68d635ac3cc6db4af6e9b6f9dd1c5cd0")
-                {
-                    content = fs::read_to_string(
-                        "test-artifacts/simulate-api-call/manifest-amd64-operator.json",
-                    )
-                    .expect("should read test (albo) operator manifest file");
-                }
-                if url.contains("422e4fbe1ed81c79084f43a826dc0674510a7ff578e62b4ddda119ed3266d0b6")
-                {
-                    content = fs::read_to_string(
-                        "test-artifacts/simulate-api-call/manifest-amd64-kube.json",
-                    )
-                    .expect("should read test (openshift) kube-proxy manifest file");
-                }
-
-                Ok(content)
-            }
-
-            async fn get_blob(
-                &self,
-                _dir: String,
-                _url: String,
-                _token: String,
-                _verify_blob: bool,
-                _blob_sum: String,
-            ) -> Result<(), MirrorError> {
-                info!("testing logging in fake test");
-                Ok(())
-            }
-        }
-
-        let fake = Fake {};
+        let fake = catalog_fake("{}");
 
         let ops = vec![op.clone()];
         let res = aw!(get_operator_catalog(
             fake.clone(),
-            String::from("./test-artifacts/"),
+            base.to_string(),
             false,
             false,
-            ops.clone()
+            ops.clone(),
+            ViewConfig::new(Some(format!("{}/state.json", base))),
+            Retention::default()
         ));
-        println!("result -> {}", res.is_ok());
+        if res.is_err() {
+            error!("result -> {}", res.as_ref().err().unwrap().to_string());
+        }
+        assert!(res.is_ok());
+        fs::remove_dir_all(base).expect("should delete collector test folder");
+    }
+
+    #[test]
+    fn get_operator_catalog_single_arch_pass() {
+        let base = "test-artifacts/collector-arm64-test";
+        fs::create_dir_all(base).expect("should create collector test folder");
+        let server = mockito::Server::new();
+        let op = Operator {
+            catalog: server.url().replace("http://", "") + "/test/test-index-operator:v1.0",
+            packages: None,
+        };
+        // single arm64 v2 manifest, the architecture is only in the config
+        let fake = catalog_fake("{\"architecture\":\"arm64\",\"os\":\"linux\"}");
+        let cfg_impl = ViewConfig::new(Some(format!("{}/state.json", base)));
+        let res = aw!(get_operator_catalog(
+            fake,
+            base.to_string(),
+            false,
+            false,
+            vec![op],
+            cfg_impl.clone(),
+            Retention::default()
+        ));
+        assert!(res.is_ok());
+        let state = cfg_impl.read_state().unwrap();
+        assert!(state
+            .get_arch("test-index-operator:v1.0", "amd64")
+            .is_none());
+        let arch = state
+            .get_arch("test-index-operator:v1.0", "arm64")
+            .expect("should register the arm64 catalog");
+        assert!(arch
+            .configs_dir
+            .contains("collector-arm64-test/test-index-operator/v1.0/arm64/cache/"));
+        fs::remove_dir_all(base).expect("should delete collector test folder");
+    }
+
+    #[test]
+    fn is_manifest_list_pass() {
+        let list = fs::read_to_string("test-artifacts/simulate-api-call/manifest-list.json")
+            .expect("should read manifest-list file");
+        let single = fs::read_to_string("test-artifacts/simulate-api-call/manifest-amd64.json")
+            .expect("should read manifest file");
+        assert!(is_manifest_list(list));
+        assert!(!is_manifest_list(single));
+        assert!(!is_manifest_list("not json".to_string()));
+    }
+
+    #[test]
+    fn get_config_arch_pass() {
+        let dir = "test-artifacts/config-arch-test";
+        let mut server = mockito::Server::new();
+        let url = format!("{}/v2/test/test-index/blobs/", server.url());
+        let reg_impl = ImplDownloadImageInterface {};
+        let client = blob_client(true).unwrap();
+        let config = "{\"architecture\": \"arm64\", \"os\": \"linux\"}".to_string();
+        let config_digest = get_digest(config.clone());
+        server
+            .mock(
+                "GET",
+                format!("/v2/test/test-index/blobs/{}", config_digest).as_str(),
+            )
+            .with_status(200)
+            .with_body(&config)
+            .create();
+        let manifest = serde_json::json!({
+            "schemaVersion": 2,
+            "config": { "digest": config_digest, "size": config.len() },
+            "layers": []
+        })
+        .to_string();
+        let res = aw!(get_config_arch(
+            &reg_impl, &client, dir, &url, "", &manifest
+        ));
+        assert_eq!(res.unwrap(), "arm64");
+        // manifests without config fall back to the manifest architecture
+        let v1 = "{\"schemaVersion\": 1, \"architecture\": \"s390x\"}";
+        let res = aw!(get_config_arch(&reg_impl, &client, dir, &url, "", v1));
+        assert_eq!(res.unwrap(), "s390x");
+        fs::remove_dir_all(dir).expect("should delete config arch test folder");
+    }
+
+    #[test]
+    fn get_manifest_arch_pass() {
+        let v1 = fs::read_to_string("test-artifacts/simulate-api-call/manifest-release-index.json")
+            .expect("should read schema v1 manifest file");
+        assert_eq!(get_manifest_arch(v1), "amd64");
+        let v1_arm = "{\"schemaVersion\": 1, \"architecture\": \"arm64\"}".to_string();
+        assert_eq!(get_manifest_arch(v1_arm), "arm64");
+        let v2 = fs::read_to_string("test-artifacts/simulate-api-call/manifest-amd64.json")
+            .expect("should read manifest file");
+        assert_eq!(get_manifest_arch(v2), "amd64");
     }
 }
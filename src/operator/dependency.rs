@@ -0,0 +1,308 @@
+use crate::operator::bundle::*;
+use crate::operator::catalog::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, HashMap, HashSet};
+
+/// declarative config maps of every package in a catalog (package -> name=schema map)
+#[derive(Debug, Clone, Default)]
+pub struct CatalogIndex {
+    pub packages: BTreeMap<String, HashMap<String, DeclarativeConfig>>,
+}
+
+impl CatalogIndex {
+    /// load every package of the catalog (configs_dir is the path registered in the state file)
+    pub fn load(configs_dir: &str) -> Result<Self, MirrorError> {
+        let mut packages = BTreeMap::new();
+        for pkg in DeclarativeConfig::get_packages(&configs_dir.to_string())?.iter() {
+            packages.insert(pkg.clone(), get_package_config(configs_dir, pkg));
+        }
+        Ok(Self { packages })
+    }
+
+    /// bundles (package, bundle) that provide the gvk (olm.gvk), highest version first per package
+    pub fn providers(&self, gvk: &Gvk) -> Vec<(String, String)> {
+        let mut providers: Vec<(String, String)> = vec![];
+        for (pkg, dc_map) in self.packages.iter() {
+            let mut bundles = get_bundles(dc_map)
+                .into_iter()
+                .filter(|(_, dc)| get_gvks(dc, "olm.gvk").contains(gvk))
+                .map(|(name, _)| name)
+                .collect::<Vec<String>>();
+            bundles.sort_by_key(|b| std::cmp::Reverse(get_bundle_version(dc_map, b)));
+            providers.extend(bundles.into_iter().map(|b| (pkg.clone(), b)));
+        }
+        providers
+    }
+
+    /// pick the bundle used to satisfy a requirement on a package
+    // the head of the default channel is preferred (as OLM does), otherwise the highest version in range
+    fn select_bundle(&self, pkg: &str, range: Option<&VersionRange>) -> Option<String> {
+        let dc_map = self.packages.get(pkg)?;
+        let in_range = |name: &str| match (range, get_bundle_version(dc_map, name)) {
+            (None, _) => true,
+            (Some(range), Some(version)) => range.matches(&version),
+            (Some(_), None) => false,
+        };
+        let head = get_default_channel(dc_map)
+            .and_then(|c| get_channels(dc_map).get(&c).cloned())
+            .and_then(|entries| get_channel_head(dc_map, &entries));
+        if let Some(head) = head.filter(|h| in_range(h)) {
+            return Some(head);
+        }
+        get_bundles(dc_map)
+            .keys()
+            .filter(|b| in_range(b))
+            .max_by_key(|b| get_bundle_version(dc_map, b))
+            .cloned()
+    }
+}
+
+/// type of a requirement (olm.package.required or olm.gvk.required)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "lowercase")]
+pub enum RequirementKind {
+    Package,
+    Gvk,
+}
+
+/// resolved requirement of a bundle (the bundle's own requirements are its children)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DependencyNode {
+    pub kind: RequirementKind,
+    // package name and version range, or group/version/kind
+    pub requirement: String,
+    pub package: Option<String>,
+    pub bundle: Option<String>,
+    // set when the requirement can't be satisfied from the catalog
+    pub error: Option<String>,
+    // the bundle was already resolved higher up in the tree (its requirements are not repeated)
+    pub repeated: bool,
+    pub children: Vec<DependencyNode>,
+}
+
+/// transitive dependencies of a bundle
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct DependencyTree {
+    pub package: String,
+    pub bundle: String,
+    pub dependencies: Vec<DependencyNode>,
+}
+
+impl DependencyTree {
+    /// get the requirements that can't be satisfied (requirement, reason)
+    pub fn unsatisfied(&self) -> Vec<(String, String)> {
+        fn walk(nodes: &[DependencyNode], res: &mut Vec<(String, String)>) {
+            for node in nodes.iter() {
+                if let Some(err) = node.error.as_ref() {
+                    res.push((node.requirement.clone(), err.clone()));
+                }
+                walk(&node.children, res);
+            }
+        }
+        let mut res = vec![];
+        walk(&self.dependencies, &mut res);
+        res
+    }
+}
+
+/// recursively resolve the required packages (honoring versionRange) and required gvks of a bundle
+// gvks are resolved against the olm.gvk properties of the other packages in the catalog,
+// a package already in the tree is preferred when more than one package provides the gvk
+pub fn resolve_dependencies(
+    index: &CatalogIndex,
+    package: &str,
+    bundle: &str,
+) -> Result<DependencyTree, MirrorError> {
+    let found = index
+        .packages
+        .get(package)
+        .map(|dc_map| dc_map.contains_key(&format!("{}=olm.bundle", bundle)))
+        .unwrap_or(false);
+    if !found {
+        return Err(MirrorError::new(&format!(
+            "bundle {} not found in package {}",
+            bundle, package
+        )));
+    }
+    let mut resolved: HashSet<(String, String)> = HashSet::new();
+    resolved.insert((package.to_string(), bundle.to_string()));
+    let dependencies = resolve_bundle(index, package, bundle, &mut resolved);
+    Ok(DependencyTree {
+        package: package.to_string(),
+        bundle: bundle.to_string(),
+        dependencies,
+    })
+}
+
+fn resolve_bundle(
+    index: &CatalogIndex,
+    package: &str,
+    bundle: &str,
+    resolved: &mut HashSet<(String, String)>,
+) -> Vec<DependencyNode> {
+    let dc = match index
+        .packages
+        .get(package)
+        .and_then(|dc_map| dc_map.get(&format!("{}=olm.bundle", bundle)))
+    {
+        Some(dc) => dc.clone(),
+        None => return vec![],
+    };
+    let mut nodes: Vec<(DependencyNode, Option<(String, String)>)> = vec![];
+    for required in get_required_packages(&dc).iter() {
+        let range_str = required.version_range.clone().unwrap_or_default();
+        let mut node = DependencyNode {
+            kind: RequirementKind::Package,
+            requirement: format!("{} {}", required.package_name, range_str)
+                .trim()
+                .to_string(),
+            package: Some(required.package_name.clone()),
+            bundle: None,
+            error: None,
+            repeated: false,
+            children: vec![],
+        };
+        let range = match range_str.is_empty() {
+            true => None,
+            false => VersionRange::parse(&range_str),
+        };
+        if !range_str.is_empty() && range.is_none() {
+            node.error = Some(format!("invalid versionRange {}", range_str));
+        } else if !index.packages.contains_key(&required.package_name) {
+            node.error = Some("package not found in catalog".to_string());
+        } else {
+            match index.select_bundle(&required.package_name, range.as_ref()) {
+                Some(b) => node.bundle = Some(b),
+                None => node.error = Some("no bundle in versionRange".to_string()),
+            }
+        }
+        let key = node
+            .bundle
+            .clone()
+            .map(|b| (required.package_name.clone(), b));
+        nodes.push((node, key));
+    }
+    for gvk in get_gvks(&dc, "olm.gvk.required").iter() {
+        let mut node = DependencyNode {
+            kind: RequirementKind::Gvk,
+            requirement: gvk.to_string(),
+            package: None,
+            bundle: None,
+            error: None,
+            repeated: false,
+            children: vec![],
+        };
+        let providers = index
+            .providers(gvk)
+            .into_iter()
+            .filter(|(p, _)| p != package)
+            .collect::<Vec<(String, String)>>();
+        let chosen = providers
+            .iter()
+            .find(|p| resolved.contains(p))
+            .or(providers
+                .iter()
+                .find(|p| nodes.iter().any(|(_, k)| k.as_ref() == Some(p))))
+            .or(providers.first())
+            .cloned();
+        let key = match chosen {
+            Some((p, b)) => {
+                node.package = Some(p.clone());
+                node.bundle = Some(b.clone());
+                Some((p, b))
+            }
+            None => {
+                node.error = Some("no package in the catalog provides this gvk".to_string());
+                None
+            }
+        };
+        nodes.push((node, key));
+    }
+    let mut res = vec![];
+    for (mut node, key) in nodes.into_iter() {
+        if let Some((p, b)) = key {
+            if !resolved.insert((p.clone(), b.clone())) {
+                node.repeated = true;
+            } else {
+                node.children = resolve_bundle(index, &p, &b, resolved);
+            }
+        }
+        res.push(node);
+    }
+    res
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    fn index() -> CatalogIndex {
+        catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ])
+    }
+
+    #[test]
+    fn resolve_dependencies_pass() {
+        let index = index();
+        let tree = resolve_dependencies(&index, "albo-operator", "albo-operator.v1.2.0").unwrap();
+        assert_eq!(tree.dependencies.len(), 2);
+        let cert = &tree.dependencies[0];
+        assert_eq!(cert.requirement, "cert-manager-operator >=1.12.0 <2.0.0");
+        assert_eq!(
+            cert.bundle,
+            Some("cert-manager-operator.v1.12.0".to_string())
+        );
+        // cert-manager requires monitoring-operator which requires cert-manager again
+        let monitoring = &cert.children[0];
+        assert_eq!(
+            monitoring.bundle,
+            Some("monitoring-operator.v0.5.0".to_string())
+        );
+        assert!(monitoring.children[0].repeated);
+        assert_eq!(monitoring.children[1].kind, RequirementKind::Gvk);
+        assert!(monitoring.children[1].error.is_some());
+        // the gvk is provided by both packages, the one already in the tree wins
+        let gvk = &tree.dependencies[1];
+        assert_eq!(gvk.requirement, "monitoring.example.com/v1/ServiceMonitor");
+        assert_eq!(gvk.package, Some("cert-manager-operator".to_string()));
+        assert!(gvk.repeated);
+        assert_eq!(
+            tree.unsatisfied(),
+            vec![(
+                "logging.example.com/v1/LogForwarder".to_string(),
+                "no package in the catalog provides this gvk".to_string()
+            )]
+        );
+
+        // the default channel head is preferred when it is in range
+        let tree = resolve_dependencies(&index, "albo-operator", "albo-operator.v1.1.0").unwrap();
+        assert_eq!(
+            tree.dependencies[0].bundle,
+            Some("cert-manager-operator.v1.12.0".to_string())
+        );
+        assert!(resolve_dependencies(&index, "albo-operator", "albo-operator.v9.0.0").is_err());
+    }
+
+    #[test]
+    fn resolve_dependencies_fail() {
+        let mut index = index();
+        index.packages.remove("cert-manager-operator");
+        let tree = resolve_dependencies(&index, "albo-operator", "albo-operator.v1.2.0").unwrap();
+        assert_eq!(
+            tree.dependencies[0].error,
+            Some("package not found in catalog".to_string())
+        );
+        // the gvk falls back to the remaining provider
+        assert_eq!(
+            tree.dependencies[1].bundle,
+            Some("monitoring-operator.v0.5.0".to_string())
+        );
+        assert_eq!(tree.unsatisfied().len(), 3);
+    }
+}
@@ -0,0 +1,148 @@
+use crate::operator::bundle::*;
+use crate::operator::catalog::*;
+use crate::operator::dependency::{CatalogIndex, RequirementKind};
+use crate::operator::provides::GvkIndex;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, BTreeSet};
+
+/// bundle of another package that depends on a package
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Dependent {
+    pub package: String,
+    pub bundle: String,
+    pub kind: RequirementKind,
+    // package name and version range, or group/version/kind
+    pub requirement: String,
+    // other packages that provide the required gvk (always empty for package requirements)
+    pub alternatives: Vec<String>,
+    // the requirement can't be satisfied once the package is dropped
+    pub breaks: bool,
+}
+
+/// reverse index of olm.package.required and olm.gvk.required (package -> dependents)
+#[derive(Debug, Clone, Default)]
+pub struct ReverseIndex {
+    pub dependents: BTreeMap<String, Vec<Dependent>>,
+}
+
+impl ReverseIndex {
+    /// build the reverse index, gvk requirements are mapped to every package that provides the gvk
+    pub fn new(catalog: &CatalogIndex, gvks: &GvkIndex) -> Self {
+        let mut dependents: BTreeMap<String, Vec<Dependent>> = BTreeMap::new();
+        for (pkg, dc_map) in catalog.packages.iter() {
+            for (bundle, dc) in get_bundles(dc_map).iter() {
+                for required in get_required_packages(dc).iter() {
+                    if &required.package_name == pkg {
+                        continue;
+                    }
+                    let requirement = format!(
+                        "{} {}",
+                        required.package_name,
+                        required.version_range.clone().unwrap_or_default()
+                    );
+                    dependents
+                        .entry(required.package_name.clone())
+                        .or_default()
+                        .push(Dependent {
+                            package: pkg.clone(),
+                            bundle: bundle.clone(),
+                            kind: RequirementKind::Package,
+                            requirement: requirement.trim().to_string(),
+                            alternatives: vec![],
+                            breaks: true,
+                        });
+                }
+                for gvk in get_gvks(dc, "olm.gvk.required").iter() {
+                    let providers = gvks
+                        .providers
+                        .get(gvk)
+                        .map(|p| {
+                            p.iter()
+                                .map(|p| p.package.clone())
+                                .filter(|p| p != pkg)
+                                .collect::<BTreeSet<String>>()
+                        })
+                        .unwrap_or_default();
+                    for provider in providers.iter() {
+                        let alternatives = providers
+                            .iter()
+                            .filter(|p| p != &provider)
+                            .cloned()
+                            .collect::<Vec<String>>();
+                        dependents
+                            .entry(provider.clone())
+                            .or_default()
+                            .push(Dependent {
+                                package: pkg.clone(),
+                                bundle: bundle.clone(),
+                                kind: RequirementKind::Gvk,
+                                requirement: gvk.to_string(),
+                                breaks: alternatives.is_empty(),
+                                alternatives,
+                            });
+                    }
+                }
+            }
+        }
+        Self { dependents }
+    }
+
+    /// get the bundles of other packages that depend on the package
+    pub fn get_dependents(&self, package: &str) -> Vec<Dependent> {
+        self.dependents.get(package).cloned().unwrap_or_default()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn reverse_index_pass() {
+        let catalog = catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]);
+        let index = ReverseIndex::new(&catalog, &GvkIndex::new(&catalog));
+        let res = index.get_dependents("cert-manager-operator");
+        let res = res
+            .iter()
+            .map(|d| (d.bundle.as_str(), d.requirement.as_str(), d.breaks))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            res,
+            vec![
+                (
+                    "albo-operator.v1.1.0",
+                    "cert-manager-operator >=1.10.0",
+                    true
+                ),
+                (
+                    "albo-operator.v1.2.0",
+                    "cert-manager-operator >=1.12.0 <2.0.0",
+                    true
+                ),
+                (
+                    "albo-operator.v1.2.0",
+                    "monitoring.example.com/v1/ServiceMonitor",
+                    false
+                ),
+                (
+                    "monitoring-operator.v0.5.0",
+                    "cert-manager-operator >=1.11.0",
+                    true
+                ),
+            ]
+        );
+        let res = index.get_dependents("monitoring-operator");
+        assert_eq!(res.len(), 2);
+        assert_eq!(
+            res[0].alternatives,
+            vec!["cert-manager-operator".to_string()]
+        );
+        assert_eq!(res[1].package, "cert-manager-operator");
+        assert!(index.get_dependents("albo-operator").is_empty());
+    }
+}
@@ -0,0 +1,157 @@
+use crate::operator::bundle::parse_version;
+use mirror_config::Package;
+use semver::Version;
+use serde_derive::{Deserialize, Serialize};
+
+/// packages filter from the ImageSetConfiguration (stored per catalog in the state file)
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageFilter {
+    pub name: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub channels: Vec<ChannelFilter>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_version: Option<String>,
+}
+
+/// channel filter (and version constraints) of a package
+#[derive(Serialize, Deserialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelFilter {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub min_version: Option<String>,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub max_version: Option<String>,
+}
+
+impl PackageFilter {
+    /// convert the mirror config package
+    pub fn from_config(pkg: &Package) -> Self {
+        let channels = pkg
+            .channels
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .map(|c| ChannelFilter {
+                name: c.name.clone(),
+                min_version: c.min_version.clone(),
+                max_version: c.max_version.clone(),
+            })
+            .collect();
+        Self {
+            name: pkg.name.clone(),
+            channels,
+            min_version: pkg.min_version.clone(),
+            max_version: pkg.max_version.clone(),
+        }
+    }
+
+    /// check if the channel is included (all channels are included when none are configured)
+    pub fn includes_channel(&self, channel: &str) -> bool {
+        self.channels.is_empty() || self.channels.iter().any(|c| c.name == channel)
+    }
+
+    /// check if the bundle version is within the configured min/max version of the channel
+    // channel constraints take precedence over the package constraints
+    // bundles with an unknown version are always included
+    pub fn includes_version(&self, channel: &str, version: Option<&Version>) -> bool {
+        let version = match version {
+            Some(v) => v,
+            None => return true,
+        };
+        let (min, max) = match self.channels.iter().find(|c| c.name == channel) {
+            Some(c) if c.min_version.is_some() || c.max_version.is_some() => {
+                (c.min_version.clone(), c.max_version.clone())
+            }
+            _ => (self.min_version.clone(), self.max_version.clone()),
+        };
+        if let Some(min) = min.as_deref().and_then(parse_version) {
+            if version < &min {
+                return false;
+            }
+        }
+        if let Some(max) = max.as_deref().and_then(parse_version) {
+            if version > &max {
+                return false;
+            }
+        }
+        true
+    }
+}
+
+/// get the filter for the given package
+pub fn find_filter<'a>(filters: &'a [PackageFilter], name: &str) -> Option<&'a PackageFilter> {
+    filters.iter().find(|f| f.name == name)
+}
+
+/// restrict the packages to the ones configured (all packages when no filter is set)
+pub fn filter_packages(packages: &[String], filters: &[PackageFilter]) -> Vec<String> {
+    if filters.is_empty() {
+        return packages.to_vec();
+    }
+    packages
+        .iter()
+        .filter(|p| find_filter(filters, p).is_some())
+        .cloned()
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    fn filters() -> Vec<PackageFilter> {
+        vec![
+            PackageFilter {
+                name: "odf-operator".to_string(),
+                channels: vec![ChannelFilter {
+                    name: "stable-4.16".to_string(),
+                    min_version: Some("4.16.2".to_string()),
+                    max_version: None,
+                }],
+                min_version: None,
+                max_version: None,
+            },
+            PackageFilter {
+                name: "3scale-operator".to_string(),
+                channels: vec![],
+                min_version: Some("0.10.0".to_string()),
+                max_version: Some("0.11.0".to_string()),
+            },
+        ]
+    }
+
+    #[test]
+    fn filter_packages_pass() {
+        let packages = vec![
+            "3scale-operator".to_string(),
+            "amq-streams".to_string(),
+            "odf-operator".to_string(),
+        ];
+        assert_eq!(
+            filter_packages(&packages, &filters()),
+            vec!["3scale-operator".to_string(), "odf-operator".to_string()]
+        );
+        assert_eq!(filter_packages(&packages, &[]).len(), 3);
+    }
+
+    #[test]
+    fn includes_pass() {
+        let f = filters();
+        let odf = find_filter(&f, "odf-operator").unwrap();
+        assert!(odf.includes_channel("stable-4.16"));
+        assert!(!odf.includes_channel("stable-4.15"));
+        assert!(odf.includes_version("stable-4.16", Some(&Version::new(4, 16, 2))));
+        assert!(!odf.includes_version("stable-4.16", Some(&Version::new(4, 16, 1))));
+        assert!(odf.includes_version("stable-4.16", None));
+
+        let threescale = find_filter(&f, "3scale-operator").unwrap();
+        assert!(threescale.includes_channel("threescale-2.13"));
+        assert!(threescale.includes_version("threescale-2.13", Some(&Version::new(0, 10, 5))));
+        assert!(!threescale.includes_version("threescale-2.13", Some(&Version::new(0, 11, 1))));
+    }
+}
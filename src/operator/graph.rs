@@ -0,0 +1,315 @@
+use crate::operator::bundle::{get_bundle_version, VersionRange};
+use crate::operator::catalog::get_channel_head;
+use mirror_catalog::{ChannelEntry, DeclarativeConfig};
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
+
+/// how an upgrade edge is declared in the channel
+// the order is used to keep the strongest declaration when an edge is declared more than once
+#[derive(Serialize, Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "camelCase")]
+pub enum EdgeKind {
+    Replaces,
+    Skips,
+    SkipRange,
+}
+
+/// upgrade edge from an installed bundle to a newer bundle
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UpgradeEdge {
+    pub from: String,
+    pub to: String,
+    pub via: EdgeKind,
+}
+
+impl fmt::Display for EdgeKind {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            EdgeKind::Replaces => write!(f, "replaces"),
+            EdgeKind::Skips => write!(f, "skips"),
+            EdgeKind::SkipRange => write!(f, "skipRange"),
+        }
+    }
+}
+
+/// get the upgrade edges from the given bundle to the entries of a channel
+// the bundle does not need to be in the channel (skipRange matches on version only)
+pub fn get_upgrade_targets(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    entries: &[ChannelEntry],
+    from: &str,
+) -> Vec<UpgradeEdge> {
+    let version = get_bundle_version(dc_map, from);
+    let mut edges: Vec<UpgradeEdge> = vec![];
+    for entry in entries.iter().filter(|e| e.name != from) {
+        let via = if entry.replaces.as_deref() == Some(from) {
+            Some(EdgeKind::Replaces)
+        } else if entry
+            .skips
+            .as_ref()
+            .map(|s| s.iter().any(|name| name == from))
+            .unwrap_or(false)
+        {
+            Some(EdgeKind::Skips)
+        } else {
+            match (
+                entry.skip_range.as_deref().and_then(VersionRange::parse),
+                version.as_ref(),
+            ) {
+                (Some(range), Some(v)) if range.matches(v) => Some(EdgeKind::SkipRange),
+                _ => None,
+            }
+        };
+        if let Some(via) = via {
+            edges.push(UpgradeEdge {
+                from: from.to_string(),
+                to: entry.name.clone(),
+                via,
+            });
+        }
+    }
+    // highest version first (the biggest jump is preferred on paths of equal length)
+    edges.sort_by(|a, b| {
+        get_bundle_version(dc_map, &b.to)
+            .cmp(&get_bundle_version(dc_map, &a.to))
+            .then(a.to.cmp(&b.to))
+    });
+    edges
+}
+
+/// bundle node of a channel graph
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GraphNode {
+    pub name: String,
+    pub version: Option<String>,
+    pub skip_range: Option<String>,
+    pub head: bool,
+}
+
+/// upgrade graph of a channel
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelGraph {
+    pub name: String,
+    pub default_channel: bool,
+    pub nodes: Vec<GraphNode>,
+    pub edges: Vec<UpgradeEdge>,
+}
+
+/// build the upgrade graph of a channel (nodes are sorted by version)
+pub fn get_channel_graph(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    channel: &str,
+    entries: &[ChannelEntry],
+    default_channel: bool,
+) -> ChannelGraph {
+    let head = get_channel_head(dc_map, entries);
+    let mut sorted = entries.to_vec();
+    sorted.sort_by(|a, b| {
+        get_bundle_version(dc_map, &a.name)
+            .cmp(&get_bundle_version(dc_map, &b.name))
+            .then(a.name.cmp(&b.name))
+    });
+    let nodes = sorted
+        .iter()
+        .map(|e| GraphNode {
+            name: e.name.clone(),
+            version: get_bundle_version(dc_map, &e.name).map(|v| v.to_string()),
+            skip_range: e.skip_range.clone(),
+            head: head.as_deref() == Some(e.name.as_str()),
+        })
+        .collect();
+    ChannelGraph {
+        name: channel.to_string(),
+        default_channel,
+        nodes,
+        edges: get_upgrade_edges(dc_map, &sorted),
+    }
+}
+
+/// get all the upgrade edges of a channel
+pub fn get_upgrade_edges(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    entries: &[ChannelEntry],
+) -> Vec<UpgradeEdge> {
+    entries
+        .iter()
+        .flat_map(|e| get_upgrade_targets(dc_map, entries, &e.name))
+        .collect()
+}
+
+/// compute the shortest upgrade path between two bundles within a channel
+// breadth first search over the replaces, skips and skipRange edges
+pub fn get_upgrade_path(
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    channel: &str,
+    entries: &[ChannelEntry],
+    from: &str,
+    to: &str,
+) -> Result<Vec<UpgradeEdge>, MirrorError> {
+    if !entries.iter().any(|e| e.name == to) {
+        let names = entries
+            .iter()
+            .map(|e| format!("  {}", e.name))
+            .collect::<Vec<String>>();
+        return Err(MirrorError::new(&format!(
+            "bundle \"{}\" not found in channel \"{}\", valid bundles are :\n{}",
+            to,
+            channel,
+            names.join("\n")
+        )));
+    }
+    if from == to {
+        return Ok(vec![]);
+    }
+    let mut visited: HashSet<String> = HashSet::from([from.to_string()]);
+    let mut previous: HashMap<String, UpgradeEdge> = HashMap::new();
+    let mut queue: VecDeque<String> = VecDeque::from([from.to_string()]);
+    while let Some(current) = queue.pop_front() {
+        for edge in get_upgrade_targets(dc_map, entries, &current) {
+            if !visited.insert(edge.to.clone()) {
+                continue;
+            }
+            let next = edge.to.clone();
+            previous.insert(next.clone(), edge);
+            if next == to {
+                let mut path: Vec<UpgradeEdge> = vec![];
+                let mut name = next;
+                while let Some(edge) = previous.get(&name) {
+                    name = edge.from.clone();
+                    path.push(edge.clone());
+                }
+                path.reverse();
+                return Ok(path);
+            }
+            queue.push_back(next);
+        }
+    }
+    Err(MirrorError::new(&format!(
+        "no upgrade path from \"{}\" to \"{}\" in channel \"{}\"",
+        from, to, channel
+    )))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::*;
+
+    fn path_names(path: &[UpgradeEdge]) -> Vec<String> {
+        path.iter()
+            .map(|e| format!("{} ({})", e.to, e.via))
+            .collect()
+    }
+
+    #[test]
+    fn get_upgrade_edges_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        let entries = channels.get("stable-v1").unwrap();
+        let targets = get_upgrade_targets(&dc_map, entries, "albo-operator.v1.0.0");
+        assert_eq!(
+            path_names(&targets),
+            vec![
+                "albo-operator.v1.2.0 (skipRange)",
+                "albo-operator.v1.1.0 (skips)",
+                "albo-operator.v1.0.1 (replaces)",
+            ]
+        );
+        assert_eq!(get_upgrade_edges(&dc_map, entries).len(), 6);
+    }
+
+    #[test]
+    fn get_channel_graph_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        let graph = get_channel_graph(
+            &dc_map,
+            "stable-v1",
+            channels.get("stable-v1").unwrap(),
+            true,
+        );
+        assert_eq!(graph.nodes.len(), 4);
+        assert_eq!(graph.nodes[0].version, Some("1.0.0".to_string()));
+        assert!(graph.nodes[3].head);
+        assert_eq!(graph.nodes.iter().filter(|n| n.head).count(), 1);
+        assert_eq!(graph.edges.len(), 6);
+    }
+
+    #[test]
+    fn get_upgrade_path_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        let entries = channels.get("stable-v1").unwrap();
+        let path = get_upgrade_path(
+            &dc_map,
+            "stable-v1",
+            entries,
+            "albo-operator.v1.0.0",
+            "albo-operator.v1.2.0",
+        )
+        .unwrap();
+        assert_eq!(path_names(&path), vec!["albo-operator.v1.2.0 (skipRange)"]);
+        // v0.9.0 is not in the channel, it can only enter through a skipRange
+        let path = get_upgrade_path(
+            &dc_map,
+            "stable-v1",
+            entries,
+            "albo-operator.v0.9.0",
+            "albo-operator.v1.1.0",
+        )
+        .unwrap();
+        assert_eq!(
+            path_names(&path),
+            vec![
+                "albo-operator.v1.0.1 (skipRange)",
+                "albo-operator.v1.1.0 (replaces)",
+            ]
+        );
+        let path = get_upgrade_path(
+            &dc_map,
+            "stable-v1",
+            entries,
+            "albo-operator.v1.1.0",
+            "albo-operator.v1.1.0",
+        )
+        .unwrap();
+        assert!(path.is_empty());
+    }
+
+    #[test]
+    fn get_upgrade_path_fail() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        let entries = channels.get("stable-v1").unwrap();
+        // downgrades are not supported
+        let res = get_upgrade_path(
+            &dc_map,
+            "stable-v1",
+            entries,
+            "albo-operator.v1.2.0",
+            "albo-operator.v1.0.1",
+        );
+        assert!(res.err().unwrap().to_string().contains("no upgrade path"));
+        let res = get_upgrade_path(
+            &dc_map,
+            "stable-v1",
+            entries,
+            "albo-operator.v1.0.0",
+            "albo-operator.v0.9.0",
+        );
+        assert!(res
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("  albo-operator.v1.2.0"));
+    }
+}
@@ -0,0 +1,644 @@
+use crate::batch::blob::file_digest;
+use crate::cli::config::{get_digest, ViewConfig};
+use crate::operator::collector::*;
+use crate::operator::snapshot::Retention;
+use custom_logger::*;
+use flate2::write::GzEncoder;
+use flate2::Compression;
+use mirror_catalog_index::untar_layers;
+use mirror_error::MirrorError;
+use mirror_utils::{parse_image, FsLayer};
+use serde_derive::Deserialize;
+use std::fs::{self, File};
+use std::io::{self, Read};
+use std::path::{Path, PathBuf};
+
+/// layer file of an image read from disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalLayer {
+    // digest from the manifest (docker archives only have the file path)
+    pub digest: Option<String>,
+    pub path: PathBuf,
+}
+
+/// single architecture manifest of an image read from disk
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalManifest {
+    pub arch: String,
+    // digest of the manifest (docker archives have no manifest, the digest of the config is used)
+    pub digest: String,
+    pub layers: Vec<LocalLayer>,
+}
+
+/// catalog image read from an OCI image layout or a docker archive
+#[derive(Debug, Clone, PartialEq)]
+pub struct LocalImage {
+    // image reference recorded in the archive (if any)
+    pub reference: Option<String>,
+    pub digest: String,
+    pub manifests: Vec<LocalManifest>,
+}
+
+/// entry of the manifest.json of a docker archive ('docker save')
+#[derive(Deserialize, Debug, Clone)]
+#[serde(rename_all = "PascalCase")]
+struct DockerManifest {
+    config: String,
+    repo_tags: Option<Vec<String>>,
+    layers: Vec<String>,
+}
+
+/// check the gzip magic bytes of a file
+fn is_gzip(path: &Path) -> Result<bool, MirrorError> {
+    let mut magic = [0u8; 2];
+    let read = File::open(path)
+        .and_then(|mut f| f.read(&mut magic))
+        .map_err(|e| MirrorError::new(&format!("reading file {:?} {}", path, e)))?;
+    Ok(read == 2 && magic == [0x1f, 0x8b])
+}
+
+fn read_json(path: &Path) -> Result<(String, serde_json::Value), MirrorError> {
+    let contents = fs::read_to_string(path)
+        .map_err(|e| MirrorError::new(&format!("reading file {:?} {}", path, e)))?;
+    let value = serde_json::from_str(&contents)
+        .map_err(|e| MirrorError::new(&format!("parsing file {:?} {}", path, e)))?;
+    Ok((contents, value))
+}
+
+/// get the path of a blob in an OCI layout (blobs/<algorithm>/<hex>)
+fn blob_path(root: &Path, digest: &str) -> Result<PathBuf, MirrorError> {
+    match digest.split_once(':') {
+        Some((algorithm, hex))
+            if !algorithm.is_empty()
+                && !hex.is_empty()
+                && hex.chars().all(|c| c.is_ascii_hexdigit()) =>
+        {
+            Ok(root.join("blobs").join(algorithm).join(hex))
+        }
+        _ => Err(MirrorError::new(&format!("invalid digest {}", digest))),
+    }
+}
+
+/// extract a tar archive (optionally gzip compressed) to the directory
+pub fn extract_archive(archive: &Path, dir: &Path) -> Result<(), MirrorError> {
+    let file = File::open(archive)
+        .map_err(|e| MirrorError::new(&format!("opening archive {:?} {}", archive, e)))?;
+    let reader: Box<dyn Read> = match is_gzip(archive)? {
+        true => Box::new(flate2::read::GzDecoder::new(file)),
+        false => Box::new(file),
+    };
+    fs::create_dir_all(dir)
+        .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", dir, e)))?;
+    tar::Archive::new(reader)
+        .unpack(dir)
+        .map_err(|e| MirrorError::new(&format!("extracting archive {:?} {}", archive, e)))
+}
+
+/// collect the image manifests of an image index (nested indexes are followed)
+fn collect_manifests(
+    root: &Path,
+    index: &serde_json::Value,
+    manifests: &mut Vec<LocalManifest>,
+) -> Result<(), MirrorError> {
+    for m in index["manifests"].as_array().into_iter().flatten() {
+        let digest = m["digest"].as_str().unwrap_or_default();
+        let (contents, value) = read_json(&blob_path(root, digest)?)?;
+        if is_manifest_list(contents) {
+            collect_manifests(root, &value, manifests)?;
+            continue;
+        }
+        let arch = match m["platform"]["architecture"].as_str() {
+            // attestation manifests (buildkit) use unknown/unknown as platform
+            Some("unknown") => continue,
+            Some(arch) => arch.to_string(),
+            None => match value["config"]["digest"].as_str() {
+                Some(config) => read_json(&blob_path(root, config)?)?.1["architecture"]
+                    .as_str()
+                    .unwrap_or("amd64")
+                    .to_string(),
+                None => "amd64".to_string(),
+            },
+        };
+        let mut layers: Vec<LocalLayer> = vec![];
+        for l in value["layers"].as_array().into_iter().flatten() {
+            let digest = l["digest"].as_str().unwrap_or_default();
+            layers.push(LocalLayer {
+                digest: Some(digest.to_string()),
+                path: blob_path(root, digest)?,
+            });
+        }
+        manifests.push(LocalManifest {
+            arch,
+            digest: digest.to_string(),
+            layers,
+        });
+    }
+    Ok(())
+}
+
+/// read the image index of an OCI image layout
+pub fn read_oci_layout(root: &Path) -> Result<LocalImage, MirrorError> {
+    let (contents, index) = read_json(&root.join("index.json"))?;
+    // containerd (and docker) record the full reference, skopeo only the tag
+    let reference = index["manifests"]
+        .as_array()
+        .into_iter()
+        .flatten()
+        .filter_map(|m| {
+            let annotations = &m["annotations"];
+            annotations["io.containerd.image.name"]
+                .as_str()
+                .or(annotations["org.opencontainers.image.ref.name"].as_str())
+        })
+        .find(|r| r.contains('/'))
+        .map(String::from);
+    let mut manifests: Vec<LocalManifest> = vec![];
+    collect_manifests(root, &index, &mut manifests)?;
+    if manifests.is_empty() {
+        return Err(MirrorError::new(&format!(
+            "no image manifest found in {:?}",
+            root
+        )));
+    }
+    // a layout with a single image is identified by the digest of that image
+    let entries = index["manifests"].as_array().map(|m| m.len()).unwrap_or(0);
+    let digest = match entries {
+        1 => index["manifests"][0]["digest"]
+            .as_str()
+            .unwrap_or_default()
+            .to_string(),
+        _ => get_digest(contents),
+    };
+    Ok(LocalImage {
+        reference,
+        digest,
+        manifests,
+    })
+}
+
+/// read the manifest.json of a docker archive (exactly one image is expected)
+pub fn read_docker_archive(root: &Path) -> Result<LocalImage, MirrorError> {
+    let (_, value) = read_json(&root.join("manifest.json"))?;
+    let images: Vec<DockerManifest> = serde_json::from_value(value)
+        .map_err(|e| MirrorError::new(&format!("parsing docker archive manifest {}", e)))?;
+    let image = match images.as_slice() {
+        [image] => image,
+        _ => {
+            return Err(MirrorError::new(&format!(
+                "docker archive contains {} images, expected one",
+                images.len()
+            )))
+        }
+    };
+    let config = root.join(&image.config);
+    let (_, value) = read_json(&config)?;
+    let digest = file_digest(&config)?;
+    let layers = image
+        .layers
+        .iter()
+        .map(|l| LocalLayer {
+            digest: None,
+            path: root.join(l),
+        })
+        .collect();
+    Ok(LocalImage {
+        reference: image
+            .repo_tags
+            .clone()
+            .unwrap_or_default()
+            .into_iter()
+            .next(),
+        digest: digest.clone(),
+        manifests: vec![LocalManifest {
+            arch: value["architecture"]
+                .as_str()
+                .unwrap_or("amd64")
+                .to_string(),
+            digest,
+            layers,
+        }],
+    })
+}
+
+/// read a catalog image from an OCI layout directory or a tar archive (oci-archive or docker-archive)
+// archives are extracted to the staging directory first
+pub fn read_local_image(path: &str, staging: &str) -> Result<LocalImage, MirrorError> {
+    let mut root = PathBuf::from(path);
+    if root.is_file() {
+        let _ = fs::remove_dir_all(staging);
+        info!("[read_local_image] extracting {} to {}", path, staging);
+        extract_archive(&root, Path::new(staging))?;
+        root = PathBuf::from(staging);
+    }
+    // docker 25+ writes an OCI layout as well, so the index is preferred
+    if root.join("index.json").exists() {
+        read_oci_layout(&root)
+    } else if root.join("manifest.json").exists() {
+        read_docker_archive(&root)
+    } else {
+        Err(MirrorError::new(&format!(
+            "{} is not an OCI layout or docker archive",
+            path
+        )))
+    }
+}
+
+/// copy the layers of a manifest to the blobs store (<blobs_dir>/<hex[..2]>/<hex>)
+// uncompressed layers (docker save) are gzip compressed first, as for layers pulled from a registry
+pub fn store_layers(
+    blobs_dir: &str,
+    manifest: &LocalManifest,
+) -> Result<Vec<FsLayer>, MirrorError> {
+    let mut fslayers: Vec<FsLayer> = vec![];
+    for layer in manifest.layers.iter() {
+        let mut source = layer.path.clone();
+        let mut compressed: Option<PathBuf> = None;
+        if !is_gzip(&layer.path)? {
+            let tmp = Path::new(blobs_dir).join(format!(
+                "{}.gz",
+                layer.path.to_string_lossy().replace('/', "_")
+            ));
+            fs::create_dir_all(blobs_dir).map_err(|e| {
+                MirrorError::new(&format!("creating directory {} {}", blobs_dir, e))
+            })?;
+            let mut input = File::open(&layer.path)
+                .map_err(|e| MirrorError::new(&format!("opening layer {:?} {}", layer.path, e)))?;
+            let output = File::create(&tmp)
+                .map_err(|e| MirrorError::new(&format!("creating file {:?} {}", tmp, e)))?;
+            let mut encoder = GzEncoder::new(output, Compression::default());
+            io::copy(&mut input, &mut encoder)
+                .and_then(|_| encoder.finish())
+                .map_err(|e| {
+                    MirrorError::new(&format!("compressing layer {:?} {}", layer.path, e))
+                })?;
+            source = tmp.clone();
+            compressed = Some(tmp);
+        }
+        let digest = file_digest(&source)?;
+        if compressed.is_none() {
+            if let Some(expected) = layer.digest.as_ref() {
+                if expected != &digest {
+                    return Err(MirrorError::new(&format!(
+                        "layer {:?} digest mismatch, expected {} got {}",
+                        layer.path, expected, digest
+                    )));
+                }
+            }
+        }
+        let hex = digest.trim_start_matches("sha256:");
+        let dest_dir = Path::new(blobs_dir).join(&hex[..2]);
+        let dest = dest_dir.join(hex);
+        fs::create_dir_all(&dest_dir)
+            .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", dest_dir, e)))?;
+        if !dest.exists() {
+            fs::copy(&source, &dest)
+                .map_err(|e| MirrorError::new(&format!("copying layer {:?} {}", source, e)))?;
+        }
+        if let Some(tmp) = compressed {
+            let _ = fs::remove_file(tmp);
+        }
+        let size = fs::metadata(&dest).map(|m| m.len() as i64).ok();
+        // same order as the layers downloaded by get_operator_catalog
+        fslayers.insert(
+            0,
+            FsLayer {
+                blob_sum: digest,
+                original_ref: None,
+                size,
+            },
+        );
+    }
+    Ok(fslayers)
+}
+
+/// import a catalog image from an OCI layout or archive and register it for viewing
+// the layers go through the same untar, find_dir and build_updated_configs steps as 'update'
+pub async fn import_operator_catalog(
+    path: String,
+    image: Option<String>,
+    dir: String,
+    all_arch: bool,
+    cfg_impl: ViewConfig,
+    retention: Retention,
+) -> Result<String, MirrorError> {
+    info!("[import_operator_catalog] importing {}", path);
+    let staging = format!("{}/import-staging", dir);
+    let local = read_local_image(&path, &staging)?;
+    let image = image.or(local.reference.clone()).ok_or(MirrorError::new(
+        "the archive has no image reference, use --image to name the catalog",
+    ))?;
+    let ir = parse_image(image.clone());
+    let catalog_key = format!("{}:{}", ir.name, ir.version);
+    let blobs_dir = dir.clone() + "/blobs-store";
+    let mut state = cfg_impl.read_state()?;
+
+    let archs = local
+        .manifests
+        .iter()
+        .map(|m| m.arch.clone())
+        .collect::<Vec<String>>();
+    let reference_arch = if archs.contains(&"amd64".to_string()) {
+        "amd64".to_string()
+    } else {
+        archs.first().cloned().unwrap_or_default()
+    };
+    for manifest in local.manifests.iter() {
+        if !all_arch && manifest.arch != reference_arch {
+            continue;
+        }
+        let arch_dir = format!("{}/{}/{}/{}", dir, ir.name, ir.version, manifest.arch);
+        fs::create_dir_all(&arch_dir)
+            .map_err(|e| MirrorError::new(&format!("creating directory {} {}", arch_dir, e)))?;
+        let working_dir_cache = format!("{}/cache", arch_dir);
+        let current = state
+            .get_arch(&catalog_key, &manifest.arch)
+            .and_then(|a| a.digest.clone());
+        let is_changed = current.as_deref() != Some(manifest.digest.as_str())
+            || !Path::new(&working_dir_cache).exists();
+        if is_changed {
+            info!(
+                "[import_operator_catalog] importing {} layers for {}",
+                manifest.layers.len(),
+                manifest.arch
+            );
+            let fslayers = store_layers(&blobs_dir, manifest)?;
+            reset_cache(
+                &mut state,
+                &catalog_key,
+                &manifest.arch,
+                &working_dir_cache,
+                &retention,
+            );
+            untar_layers(blobs_dir.clone(), working_dir_cache.clone(), fslayers).await;
+            info!("[import_operator_catalog] completed untar of layers");
+        } else {
+            info!(
+                "[import_operator_catalog] {} ({}) is unchanged",
+                catalog_key, manifest.arch
+            );
+        }
+        register_cache(
+            &mut state,
+            &catalog_key,
+            &image,
+            &local.digest,
+            &manifest.arch,
+            &manifest.digest,
+            &working_dir_cache,
+            is_changed,
+            &retention,
+        )
+        .await;
+    }
+    cfg_impl.write_state(&state)?;
+    if Path::new(&staging).exists() {
+        let _ = fs::remove_dir_all(&staging);
+    }
+    Ok(catalog_key)
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use mirror_catalog::DeclarativeConfig;
+    use sha2::{Digest, Sha256};
+    use std::io::Write;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    // tar the files (name, contents) into an archive
+    fn tar_files(path: &Path, files: &[(&str, Vec<u8>)], gzip: bool) {
+        let file = File::create(path).expect("should create archive");
+        let writer: Box<dyn Write> = match gzip {
+            true => Box::new(GzEncoder::new(file, Compression::default())),
+            false => Box::new(file),
+        };
+        let mut builder = tar::Builder::new(writer);
+        for (name, contents) in files.iter() {
+            let mut header = tar::Header::new_gnu();
+            header.set_size(contents.len() as u64);
+            header.set_mode(0o644);
+            header.set_cksum();
+            builder
+                .append_data(&mut header, name, contents.as_slice())
+                .expect("should append file");
+        }
+        builder
+            .into_inner()
+            .expect("should finish archive")
+            .flush()
+            .unwrap();
+    }
+
+    fn write_blob(root: &Path, contents: &[u8]) -> String {
+        let digest = format!("sha256:{}", hex::encode(Sha256::digest(contents)));
+        let path = blob_path(root, &digest).unwrap();
+        fs::create_dir_all(path.parent().unwrap()).unwrap();
+        fs::write(path, contents).unwrap();
+        digest
+    }
+
+    fn catalog_layer(dir: &Path) -> Vec<u8> {
+        let catalog = fs::read("test-artifacts/fbc-configs/albo-operator/catalog.json").unwrap();
+        let layer = dir.join("layer.tar.gz");
+        tar_files(
+            &layer,
+            &[("configs/albo-operator/catalog.json", catalog)],
+            true,
+        );
+        fs::read(layer).unwrap()
+    }
+
+    #[test]
+    fn read_oci_layout_pass() {
+        let base = Path::new("test-artifacts/import-test-oci");
+        let root = base.join("layout");
+        fs::create_dir_all(&root).unwrap();
+        let layer = write_blob(&root, &catalog_layer(base));
+        let config = write_blob(&root, br#"{"architecture":"arm64","os":"linux"}"#);
+        let manifest = format!(
+            r#"{{"schemaVersion":2,"config":{{"digest":"{}"}},"layers":[{{"digest":"{}"}}]}}"#,
+            config, layer
+        );
+        let manifest = write_blob(&root, manifest.as_bytes());
+        let image_index = format!(
+            r#"{{"schemaVersion":2,"manifests":[{{"digest":"{}","platform":{{"architecture":"amd64","os":"linux"}}}},{{"digest":"{}"}}]}}"#,
+            manifest, manifest
+        );
+        let image_index = write_blob(&root, image_index.as_bytes());
+        fs::write(root.join("oci-layout"), r#"{"imageLayoutVersion":"1.0.0"}"#).unwrap();
+        fs::write(
+            root.join("index.json"),
+            format!(
+                r#"{{"schemaVersion":2,"manifests":[{{"digest":"{}","annotations":{{"org.opencontainers.image.ref.name":"registry.example.com/test/test-index:v1.0"}}}}]}}"#,
+                image_index
+            ),
+        )
+        .unwrap();
+
+        let res = read_local_image(root.to_str().unwrap(), "unused").unwrap();
+        assert_eq!(
+            res.reference,
+            Some("registry.example.com/test/test-index:v1.0".to_string())
+        );
+        assert_eq!(res.digest, image_index);
+        let archs = res
+            .manifests
+            .iter()
+            .map(|m| m.arch.as_str())
+            .collect::<Vec<&str>>();
+        // the arm64 manifest has no platform, the architecture comes from the config
+        assert_eq!(archs, vec!["amd64", "arm64"]);
+        assert_eq!(res.manifests[0].layers[0].digest, Some(layer.clone()));
+
+        // the same layout as an oci-archive
+        let archive = base.join("oci-archive.tar");
+        let mut files: Vec<(String, Vec<u8>)> = vec![];
+        for entry in walkdir::WalkDir::new(&root) {
+            let entry = entry.unwrap();
+            if entry.file_type().is_file() {
+                let name = entry.path().strip_prefix(&root).unwrap();
+                files.push((
+                    name.to_string_lossy().to_string(),
+                    fs::read(entry.path()).unwrap(),
+                ));
+            }
+        }
+        let files = files
+            .iter()
+            .map(|(n, c)| (n.as_str(), c.clone()))
+            .collect::<Vec<_>>();
+        tar_files(&archive, &files, false);
+        let staging = base.join("staging");
+        let from_archive =
+            read_local_image(archive.to_str().unwrap(), staging.to_str().unwrap()).unwrap();
+        assert_eq!(from_archive.digest, res.digest);
+        assert_eq!(from_archive.manifests.len(), 2);
+
+        let blobs_dir = base.join("blobs-store");
+        let fslayers =
+            store_layers(blobs_dir.to_str().unwrap(), &from_archive.manifests[0]).unwrap();
+        assert_eq!(fslayers[0].blob_sum, layer);
+        let hex = layer.trim_start_matches("sha256:");
+        assert!(blobs_dir.join(&hex[..2]).join(hex).exists());
+        fs::remove_dir_all(base).expect("should delete import test folder");
+    }
+
+    // docker-archive (docker save) of the test-index:v1.0 catalog with a single amd64 image
+    fn docker_archive(base: &Path) -> PathBuf {
+        let catalog = fs::read("test-artifacts/fbc-configs/albo-operator/catalog.json").unwrap();
+        let layer = base.join("layer.tar");
+        tar_files(
+            &layer,
+            &[("configs/albo-operator/catalog.json", catalog)],
+            false,
+        );
+        let archive = base.join("docker-archive.tar");
+        tar_files(
+            &archive,
+            &[
+                (
+                    "manifest.json",
+                    br#"[{"Config":"0123abcd.json","RepoTags":["registry.example.com/test/test-index:v1.0"],"Layers":["0a1b2c/layer.tar"]}]"#.to_vec(),
+                ),
+                (
+                    "0123abcd.json",
+                    br#"{"architecture":"amd64","os":"linux"}"#.to_vec(),
+                ),
+                ("0a1b2c/layer.tar", fs::read(&layer).unwrap()),
+            ],
+            false,
+        );
+        archive
+    }
+
+    #[test]
+    fn read_docker_archive_pass() {
+        let base = Path::new("test-artifacts/import-test-docker");
+        fs::create_dir_all(base).unwrap();
+        let archive = docker_archive(base);
+        let staging = base.join("staging");
+        let res = read_local_image(archive.to_str().unwrap(), staging.to_str().unwrap()).unwrap();
+        assert_eq!(
+            res.reference,
+            Some("registry.example.com/test/test-index:v1.0".to_string())
+        );
+        assert_eq!(res.manifests[0].arch, "amd64");
+        assert_eq!(res.manifests[0].layers[0].digest, None);
+
+        // uncompressed layers are stored gzip compressed
+        let blobs_dir = base.join("blobs-store");
+        let fslayers = store_layers(blobs_dir.to_str().unwrap(), &res.manifests[0]).unwrap();
+        let hex = fslayers[0]
+            .blob_sum
+            .trim_start_matches("sha256:")
+            .to_string();
+        let blob = blobs_dir.join(&hex[..2]).join(&hex);
+        assert!(is_gzip(&blob).unwrap());
+        assert_eq!(file_digest(&blob).unwrap(), fslayers[0].blob_sum);
+        fs::remove_dir_all(base).expect("should delete import test folder");
+    }
+
+    #[test]
+    fn read_local_image_fail() {
+        let base = Path::new("test-artifacts/import-test-fail");
+        let root = base.join("layout");
+        fs::create_dir_all(&root).unwrap();
+        assert!(read_local_image(root.to_str().unwrap(), "unused").is_err());
+
+        // layer content that doesn't match its digest
+        let layer = write_blob(&root, &catalog_layer(base));
+        let path = blob_path(&root, &layer).unwrap();
+        fs::write(&path, catalog_layer(base).repeat(2)).unwrap();
+        let manifest = LocalManifest {
+            arch: "amd64".to_string(),
+            digest: "sha256:00".to_string(),
+            layers: vec![LocalLayer {
+                digest: Some(layer),
+                path,
+            }],
+        };
+        let blobs_dir = base.join("blobs-store");
+        let err = store_layers(blobs_dir.to_str().unwrap(), &manifest)
+            .err()
+            .unwrap();
+        assert!(err.to_string().contains("digest mismatch"));
+        assert!(blob_path(&root, "sha256:../../etc").is_err());
+        fs::remove_dir_all(base).expect("should delete import test folder");
+    }
+
+    #[test]
+    fn import_operator_catalog_pass() {
+        let base = Path::new("test-artifacts/import-test-e2e");
+        fs::create_dir_all(base).unwrap();
+        let archive = docker_archive(base);
+        let dir = base.join("working-dir").to_string_lossy().to_string();
+        let cfg_impl = ViewConfig::new(Some(base.join("state.json").to_string_lossy().to_string()));
+        let res = aw!(import_operator_catalog(
+            archive.to_string_lossy().to_string(),
+            None,
+            dir.clone(),
+            false,
+            cfg_impl.clone(),
+            Retention::default()
+        ));
+        assert_eq!(res.unwrap(), "test-index:v1.0");
+        let state = cfg_impl.read_state().unwrap();
+        let arch = state
+            .get_arch("test-index:v1.0", "amd64")
+            .expect("should register the amd64 catalog");
+        assert!(arch
+            .configs_dir
+            .contains("import-test-e2e/working-dir/test-index/v1.0/amd64/cache/"));
+        let packages = DeclarativeConfig::get_packages(&arch.configs_dir).unwrap();
+        assert!(packages.contains(&"albo-operator".to_string()));
+        // the staging directory is removed
+        assert!(!Path::new(&format!("{}/import-staging", dir)).exists());
+        fs::remove_dir_all(base).expect("should delete import test folder");
+    }
+}
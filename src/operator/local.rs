@@ -0,0 +1,240 @@
+use crate::operator::catalog::parse_fbc_values;
+use custom_logger::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use sha2::{Digest, Sha256};
+use std::collections::BTreeMap;
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+use walkdir::WalkDir;
+
+/// base directory for prepared local catalogs
+// $XDG_CACHE_HOME/operator-catalog-viewer/fbc, $HOME/.cache/... or the temp dir
+pub fn fbc_cache_dir() -> String {
+    let cache_dir = match env::var("XDG_CACHE_HOME") {
+        Ok(dir) if !dir.is_empty() => dir,
+        _ => match env::var("HOME") {
+            Ok(home) if !home.is_empty() => format!("{}/.cache", home),
+            _ => env::temp_dir().to_string_lossy().to_string(),
+        },
+    };
+    format!("{}/operator-catalog-viewer/fbc", cache_dir)
+}
+
+/// check if the catalog argument is a local directory (instead of a registered catalog)
+pub fn is_fbc_dir(catalog: &str) -> bool {
+    Path::new(catalog).is_dir()
+}
+
+/// read every json and yaml file of a fbc directory and group the objects by package
+// hidden directories (i.e .git) and updated-configs are skipped, objects without a schema are ignored
+// returns the digest of the files read (paths and contents) and the objects of every package
+pub fn read_fbc_dir(
+    dir: &Path,
+) -> Result<(String, BTreeMap<String, Vec<serde_json::Value>>), MirrorError> {
+    let mut hasher = Sha256::new();
+    let mut packages: BTreeMap<String, Vec<serde_json::Value>> = BTreeMap::new();
+    let walker = WalkDir::new(dir)
+        .sort_by_file_name()
+        .into_iter()
+        .filter_entry(|e| {
+            let name = e.file_name().to_string_lossy();
+            e.depth() == 0 || !(name.starts_with('.') || name == "updated-configs")
+        });
+    for entry in walker {
+        let entry =
+            entry.map_err(|e| MirrorError::new(&format!("reading directory {:?} {}", dir, e)))?;
+        let yaml = match entry.path().extension().and_then(|e| e.to_str()) {
+            Some("json") => false,
+            Some("yaml") | Some("yml") => true,
+            _ => continue,
+        };
+        let contents = fs::read_to_string(entry.path())
+            .map_err(|e| MirrorError::new(&format!("reading file {:?} {}", entry.path(), e)))?;
+        hasher.update(
+            entry
+                .path()
+                .strip_prefix(dir)
+                .unwrap_or(entry.path())
+                .to_string_lossy()
+                .as_bytes(),
+        );
+        hasher.update(contents.as_bytes());
+        let values = parse_fbc_values(&contents, yaml)
+            .map_err(|e| MirrorError::new(&format!("{:?} {}", entry.path(), e)))?;
+        for value in values.into_iter() {
+            let package = match value["schema"].as_str() {
+                None => continue,
+                Some("olm.package") => value["name"].as_str(),
+                Some(_) => value["package"].as_str(),
+            };
+            match package {
+                Some(package) => packages.entry(package.to_string()).or_default().push(value),
+                None => warn!(
+                    "[read_fbc_dir] {:?} has a {} object without package",
+                    entry.path(),
+                    value["schema"]
+                ),
+            }
+        }
+    }
+    Ok((
+        format!("sha256:{}", hex::encode(hasher.finalize())),
+        packages,
+    ))
+}
+
+/// write the objects of every package to <configs_dir>/<package>/catalog.json (replaces the directory)
+pub fn write_fbc_packages(
+    configs_dir: &Path,
+    packages: &BTreeMap<String, Vec<serde_json::Value>>,
+) -> Result<(), MirrorError> {
+    let _ = fs::remove_dir_all(configs_dir);
+    for (package, values) in packages.iter() {
+        let package_dir = configs_dir.join(package);
+        fs::create_dir_all(&package_dir).map_err(|e| {
+            MirrorError::new(&format!("creating directory {:?} {}", package_dir, e))
+        })?;
+        // json stream, as written by 'opm render'
+        let mut contents = String::new();
+        for value in values.iter() {
+            let json = serde_json::to_string_pretty(value)
+                .map_err(|e| MirrorError::new(&format!("serializing fbc object {}", e)))?;
+            contents.push_str(&json);
+            contents.push('\n');
+        }
+        let file = package_dir.join("catalog.json");
+        fs::write(&file, contents)
+            .map_err(|e| MirrorError::new(&format!("writing file {:?} {}", file, e)))?;
+    }
+    Ok(())
+}
+
+/// prepare a local fbc directory (i.e a git checkout) for viewing, no image or 'update' needed
+// the objects are written per package to <cache_base>/<id>/configs/<package>/catalog.json and the
+// updated-configs are built there, so the source directory is never modified
+// the cache is only rebuilt when the contents of the directory change, returns the configs dir
+pub fn prepare_fbc_dir(dir: &str, cache_base: &str) -> Result<String, MirrorError> {
+    let source = fs::canonicalize(dir)
+        .map_err(|e| MirrorError::new(&format!("reading directory {} {}", dir, e)))?;
+    let id = hex::encode(Sha256::digest(source.to_string_lossy().as_bytes()));
+    let cache = PathBuf::from(cache_base).join(&id[..16]);
+    let configs_dir = cache.join("configs");
+    let digest_file = cache.join("source.digest");
+
+    let (digest, packages) = read_fbc_dir(&source)?;
+    if packages.is_empty() {
+        return Err(MirrorError::new(&format!(
+            "no file-based catalog objects found in {}",
+            dir
+        )));
+    }
+    let current = fs::read_to_string(&digest_file).unwrap_or_default();
+    if current.trim() == digest && configs_dir.exists() {
+        debug!("[prepare_fbc_dir] {} is unchanged", dir);
+        return Ok(format!("{}/", configs_dir.to_string_lossy()));
+    }
+
+    info!(
+        "[prepare_fbc_dir] preparing {} packages of {} in {:?}",
+        packages.len(),
+        dir,
+        cache
+    );
+    write_fbc_packages(&configs_dir, &packages)?;
+    DeclarativeConfig::build_updated_configs(configs_dir.to_string_lossy().to_string())?;
+    fs::write(&digest_file, &digest)
+        .map_err(|e| MirrorError::new(&format!("writing file {:?} {}", digest_file, e)))?;
+    Ok(format!("{}/", configs_dir.to_string_lossy()))
+}
+
+/// open a local fbc directory, returns the catalog name (<dir name>:local) and the configs dir
+pub fn open_fbc_dir(dir: &str) -> Result<(String, String), MirrorError> {
+    let configs_dir = prepare_fbc_dir(dir, &fbc_cache_dir())?;
+    let name = fs::canonicalize(dir)
+        .ok()
+        .and_then(|p| p.file_name().map(|n| n.to_string_lossy().to_string()))
+        .unwrap_or("catalog".to_string());
+    Ok((format!("{}:local", name), configs_dir))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::load_package_fbc;
+
+    #[test]
+    fn prepare_fbc_dir_pass() {
+        let base = "test-artifacts/local-fbc-test";
+        let configs_dir = prepare_fbc_dir("test-artifacts/fbc-configs", base).unwrap();
+        assert!(configs_dir.ends_with("/configs/"));
+        let configs = load_package_fbc(&configs_dir, "albo-operator").unwrap();
+        let expected = load_package_fbc("test-artifacts/fbc-configs", "albo-operator").unwrap();
+        assert_eq!(configs.len(), expected.len());
+        assert!(Path::new(&configs_dir)
+            .join("broken-operator/catalog.json")
+            .exists());
+        // unchanged sources are not prepared again
+        let digest_file = Path::new(&configs_dir)
+            .parent()
+            .unwrap()
+            .join("source.digest");
+        let modified = fs::metadata(&digest_file).unwrap().modified().unwrap();
+        assert_eq!(
+            prepare_fbc_dir("test-artifacts/fbc-configs", base).unwrap(),
+            configs_dir
+        );
+        assert_eq!(
+            fs::metadata(&digest_file).unwrap().modified().unwrap(),
+            modified
+        );
+        fs::remove_dir_all(base).expect("should delete local fbc test folder");
+    }
+
+    #[test]
+    fn read_fbc_dir_pass() {
+        let base = Path::new("test-artifacts/local-fbc-yaml-test");
+        fs::create_dir_all(base.join(".git")).unwrap();
+        fs::write(
+            base.join("index.yaml"),
+            "---
+schema: olm.package
+name: foo-operator
+defaultChannel: stable
+---
+schema: olm.channel
+package: foo-operator
+name: stable
+entries:
+  - name: foo-operator.v0.1.0
+---
+schema: olm.package
+name: bar-operator
+---
+# not a catalog object
+kind: Config
+",
+        )
+        .unwrap();
+        fs::write(
+            base.join(".git/config.json"),
+            "{\"schema\":\"olm.package\",\"name\":\"git\"}",
+        )
+        .unwrap();
+        let (_, packages) = read_fbc_dir(base).unwrap();
+        assert_eq!(
+            packages.keys().cloned().collect::<Vec<String>>(),
+            vec!["bar-operator".to_string(), "foo-operator".to_string()]
+        );
+        assert_eq!(
+            packages["foo-operator"][1]["entries"][0]["name"],
+            "foo-operator.v0.1.0"
+        );
+        fs::write(base.join("bad.json"), "{ \"schema\": ").unwrap();
+        assert!(read_fbc_dir(base).is_err());
+        assert!(prepare_fbc_dir("test-artifacts/none", "unused").is_err());
+        fs::remove_dir_all(base).expect("should delete local fbc test folder");
+    }
+}
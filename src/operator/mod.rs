@@ -1 +1,14 @@
+pub mod bundle;
+pub mod catalog;
 pub mod collector;
+pub mod dependency;
+pub mod dependents;
+pub mod filter;
+pub mod graph;
+pub mod import;
+pub mod local;
+pub mod provides;
+pub mod selection;
+pub mod snapshot;
+pub mod sqlite;
+pub mod validate;
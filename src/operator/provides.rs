@@ -0,0 +1,158 @@
+use crate::operator::bundle::*;
+use crate::operator::catalog::*;
+use crate::operator::dependency::CatalogIndex;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::BTreeMap;
+
+/// bundle that provides a gvk (olm.gvk)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct GvkProvider {
+    pub package: String,
+    pub bundle: String,
+    pub version: Option<String>,
+    // the bundle is the head of the default channel of its package
+    pub default_head: bool,
+}
+
+/// index of the olm.gvk properties of every bundle in a catalog
+#[derive(Debug, Clone, Default)]
+pub struct GvkIndex {
+    pub providers: BTreeMap<Gvk, Vec<GvkProvider>>,
+}
+
+/// gvk lookup i.e group/version/kind, group/kind or kind (case insensitive)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GvkQuery {
+    pub group: Option<String>,
+    pub version: Option<String>,
+    pub kind: String,
+}
+
+impl GvkQuery {
+    pub fn parse(query: &str) -> Result<Self, MirrorError> {
+        let parts = query.trim().split('/').collect::<Vec<&str>>();
+        if parts.iter().any(|p| p.is_empty()) {
+            return Err(MirrorError::new(&format!(
+                "invalid gvk \"{}\", expected group/version/kind, group/kind or kind",
+                query
+            )));
+        }
+        let lower = |s: &str| Some(s.to_lowercase());
+        match parts.as_slice() {
+            [kind] => Ok(Self {
+                group: None,
+                version: None,
+                kind: kind.to_lowercase(),
+            }),
+            [group, kind] => Ok(Self {
+                group: lower(group),
+                version: None,
+                kind: kind.to_lowercase(),
+            }),
+            [group, version, kind] => Ok(Self {
+                group: lower(group),
+                version: lower(version),
+                kind: kind.to_lowercase(),
+            }),
+            _ => Err(MirrorError::new(&format!(
+                "invalid gvk \"{}\", expected group/version/kind, group/kind or kind",
+                query
+            ))),
+        }
+    }
+
+    pub fn matches(&self, gvk: &Gvk) -> bool {
+        let eq = |q: &Option<String>, v: &str| q.as_ref().map(|q| q == &v.to_lowercase());
+        gvk.kind.to_lowercase() == self.kind
+            && eq(&self.group, &gvk.group).unwrap_or(true)
+            && eq(&self.version, &gvk.version).unwrap_or(true)
+    }
+}
+
+impl GvkIndex {
+    /// build the index from the declarative configs of every package
+    // providers are sorted by package name, highest version first
+    pub fn new(catalog: &CatalogIndex) -> Self {
+        let mut providers: BTreeMap<Gvk, Vec<GvkProvider>> = BTreeMap::new();
+        for (pkg, dc_map) in catalog.packages.iter() {
+            let head = get_default_channel(dc_map)
+                .and_then(|c| get_channels(dc_map).get(&c).cloned())
+                .and_then(|entries| get_channel_head(dc_map, &entries));
+            let mut bundles = get_bundles(dc_map).into_iter().collect::<Vec<_>>();
+            bundles.sort_by_key(|(name, _)| std::cmp::Reverse(get_bundle_version(dc_map, name)));
+            for (name, dc) in bundles.iter() {
+                for gvk in get_gvks(dc, "olm.gvk").into_iter() {
+                    providers.entry(gvk).or_default().push(GvkProvider {
+                        package: pkg.clone(),
+                        bundle: name.clone(),
+                        version: get_bundle_version(dc_map, name).map(|v| v.to_string()),
+                        default_head: head.as_deref() == Some(name.as_str()),
+                    });
+                }
+            }
+        }
+        Self { providers }
+    }
+
+    /// get the gvks matching the query with their providers
+    pub fn lookup(&self, query: &GvkQuery) -> Vec<(&Gvk, &Vec<GvkProvider>)> {
+        self.providers
+            .iter()
+            .filter(|(gvk, _)| query.matches(gvk))
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn gvk_index_pass() {
+        let index = GvkIndex::new(&catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]));
+        let query = GvkQuery::parse("monitoring.example.com/v1/ServiceMonitor").unwrap();
+        let res = index.lookup(&query);
+        assert_eq!(res.len(), 1);
+        let providers = res[0]
+            .1
+            .iter()
+            .map(|p| (p.bundle.as_str(), p.default_head))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            providers,
+            vec![
+                ("cert-manager-operator.v1.12.0", true),
+                ("monitoring-operator.v0.5.0", true),
+                ("monitoring-operator.v0.4.0", false),
+            ]
+        );
+        // kind only and group/kind lookups are case insensitive and match every version
+        assert_eq!(
+            index.lookup(&GvkQuery::parse("certificate").unwrap()).len(),
+            1
+        );
+        assert_eq!(
+            index
+                .lookup(&GvkQuery::parse("networking.example.com/TargetGroupBinding").unwrap())
+                .len(),
+            2
+        );
+        assert!(index
+            .lookup(&GvkQuery::parse("cert-manager.io/v2/Certificate").unwrap())
+            .is_empty());
+    }
+
+    #[test]
+    fn gvk_query_fail() {
+        assert!(GvkQuery::parse("").is_err());
+        assert!(GvkQuery::parse("a//b").is_err());
+        assert!(GvkQuery::parse("a/b/c/d").is_err());
+    }
+}
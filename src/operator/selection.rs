@@ -0,0 +1,247 @@
+use crate::operator::bundle::parse_version;
+use crate::operator::filter::{ChannelFilter, PackageFilter};
+use mirror_error::MirrorError;
+
+/// mark (or unmark) a whole package, returns true when the package is marked
+pub fn toggle_package(selection: &mut Vec<PackageFilter>, name: &str) -> bool {
+    if let Some(pos) = selection.iter().position(|f| f.name == name) {
+        selection.remove(pos);
+        return false;
+    }
+    selection.push(PackageFilter {
+        name: name.to_string(),
+        channels: vec![],
+        min_version: None,
+        max_version: None,
+    });
+    selection.sort_by(|a, b| a.name.cmp(&b.name));
+    true
+}
+
+/// get the selected package (marks it when needed)
+fn get_or_insert<'a>(selection: &'a mut Vec<PackageFilter>, name: &str) -> &'a mut PackageFilter {
+    if !selection.iter().any(|f| f.name == name) {
+        toggle_package(selection, name);
+    }
+    selection.iter_mut().find(|f| f.name == name).unwrap()
+}
+
+/// mark (or unmark) a channel of a package, returns true when the channel is marked
+// the package is unmarked when its last channel is unmarked
+pub fn toggle_channel(selection: &mut Vec<PackageFilter>, pkg: &str, channel: &str) -> bool {
+    let filter = get_or_insert(selection, pkg);
+    if let Some(pos) = filter.channels.iter().position(|c| c.name == channel) {
+        filter.channels.remove(pos);
+        if filter.channels.is_empty() {
+            toggle_package(selection, pkg);
+        }
+        return false;
+    }
+    filter.channels.push(ChannelFilter {
+        name: channel.to_string(),
+        min_version: None,
+        max_version: None,
+    });
+    filter.channels.sort_by(|a, b| a.name.cmp(&b.name));
+    true
+}
+
+/// set (or clear when unchanged) the min or max version of a channel (marks the channel)
+pub fn toggle_channel_bound(
+    selection: &mut Vec<PackageFilter>,
+    pkg: &str,
+    channel: &str,
+    version: &str,
+    max: bool,
+) {
+    let marked = selection
+        .iter()
+        .any(|f| f.name == pkg && f.channels.iter().any(|c| c.name == channel));
+    if !marked {
+        toggle_channel(selection, pkg, channel);
+    }
+    let filter = get_or_insert(selection, pkg);
+    let c = filter
+        .channels
+        .iter_mut()
+        .find(|c| c.name == channel)
+        .unwrap();
+    let bound = match max {
+        true => &mut c.max_version,
+        false => &mut c.min_version,
+    };
+    *bound = match bound.as_deref() == Some(version) {
+        true => None,
+        false => Some(version.to_string()),
+    };
+}
+
+/// parse a package selection i.e package[:channel][@min..max]
+// min or max can be omitted (i.e odf-operator:stable-4.18@4.18.2.. or odf-operator@..4.18.5),
+// a single version without '..' sets both
+pub fn parse_package_spec(spec: &str) -> Result<PackageFilter, MirrorError> {
+    let invalid = |reason: &str| {
+        MirrorError::new(&format!(
+            "invalid package \"{}\" {}, expected package[:channel][@min..max]",
+            spec, reason
+        ))
+    };
+    let (name, versions) = spec.split_once('@').unwrap_or((spec, ""));
+    let (name, channel) = match name.split_once(':') {
+        Some((name, channel)) => (name, Some(channel)),
+        None => (name, None),
+    };
+    if name.is_empty() || channel == Some("") {
+        return Err(invalid("(empty name)"));
+    }
+    let (min, max) = match versions.split_once("..") {
+        Some((min, max)) => (min, max),
+        None => (versions, versions),
+    };
+    let version = |v: &str| -> Result<Option<String>, MirrorError> {
+        match v.is_empty() {
+            true => Ok(None),
+            false => parse_version(v)
+                .map(|_| Some(v.to_string()))
+                .ok_or(invalid(&format!("(version {})", v))),
+        }
+    };
+    let (min_version, max_version) = (version(min)?, version(max)?);
+    Ok(match channel {
+        Some(channel) => PackageFilter {
+            name: name.to_string(),
+            channels: vec![ChannelFilter {
+                name: channel.to_string(),
+                min_version,
+                max_version,
+            }],
+            min_version: None,
+            max_version: None,
+        },
+        None => PackageFilter {
+            name: name.to_string(),
+            channels: vec![],
+            min_version,
+            max_version,
+        },
+    })
+}
+
+/// merge a package selection (channels of the same package are combined)
+pub fn merge_selection(selection: &mut Vec<PackageFilter>, filter: PackageFilter) {
+    match selection.iter_mut().find(|f| f.name == filter.name) {
+        Some(existing) => {
+            for channel in filter.channels.into_iter() {
+                existing.channels.retain(|c| c.name != channel.name);
+                existing.channels.push(channel);
+            }
+            existing.channels.sort_by(|a, b| a.name.cmp(&b.name));
+            existing.min_version = filter.min_version.or(existing.min_version.take());
+            existing.max_version = filter.max_version.or(existing.max_version.take());
+        }
+        None => {
+            selection.push(filter);
+            selection.sort_by(|a, b| a.name.cmp(&b.name));
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn toggle_pass() {
+        let mut selection: Vec<PackageFilter> = vec![];
+        assert!(toggle_package(&mut selection, "odf-operator"));
+        assert!(toggle_package(&mut selection, "3scale-operator"));
+        assert_eq!(selection[0].name, "3scale-operator");
+        assert!(!toggle_package(&mut selection, "3scale-operator"));
+        assert_eq!(selection.len(), 1);
+
+        assert!(toggle_channel(
+            &mut selection,
+            "odf-operator",
+            "stable-4.18"
+        ));
+        toggle_channel_bound(
+            &mut selection,
+            "odf-operator",
+            "stable-4.18",
+            "4.18.2",
+            false,
+        );
+        toggle_channel_bound(
+            &mut selection,
+            "odf-operator",
+            "stable-4.17",
+            "4.17.9",
+            true,
+        );
+        assert_eq!(selection[0].channels.len(), 2);
+        assert_eq!(
+            selection[0].channels[1].min_version,
+            Some("4.18.2".to_string())
+        );
+        assert_eq!(
+            selection[0].channels[0].max_version,
+            Some("4.17.9".to_string())
+        );
+        // setting the same bound again clears it
+        toggle_channel_bound(
+            &mut selection,
+            "odf-operator",
+            "stable-4.18",
+            "4.18.2",
+            false,
+        );
+        assert_eq!(selection[0].channels[1].min_version, None);
+
+        assert!(!toggle_channel(
+            &mut selection,
+            "odf-operator",
+            "stable-4.17"
+        ));
+        assert!(!toggle_channel(
+            &mut selection,
+            "odf-operator",
+            "stable-4.18"
+        ));
+        assert!(selection.is_empty());
+    }
+
+    #[test]
+    fn parse_package_spec_pass() {
+        let res = parse_package_spec("odf-operator:stable-4.18@4.18.2..4.18.5").unwrap();
+        assert_eq!(res.name, "odf-operator");
+        assert_eq!(res.channels[0].name, "stable-4.18");
+        assert_eq!(res.channels[0].min_version, Some("4.18.2".to_string()));
+        assert_eq!(res.channels[0].max_version, Some("4.18.5".to_string()));
+
+        let res = parse_package_spec("3scale-operator@..0.11.0").unwrap();
+        assert!(res.channels.is_empty());
+        assert_eq!(res.min_version, None);
+        assert_eq!(res.max_version, Some("0.11.0".to_string()));
+
+        let res = parse_package_spec("albo-operator@1.2.0").unwrap();
+        assert_eq!(res.min_version, res.max_version);
+
+        let mut selection = vec![res];
+        merge_selection(
+            &mut selection,
+            parse_package_spec("albo-operator:stable-v1").unwrap(),
+        );
+        assert_eq!(selection.len(), 1);
+        assert_eq!(selection[0].channels.len(), 1);
+        assert_eq!(selection[0].min_version, Some("1.2.0".to_string()));
+    }
+
+    #[test]
+    fn parse_package_spec_fail() {
+        assert!(parse_package_spec("").is_err());
+        assert!(parse_package_spec("odf-operator:").is_err());
+        let res = parse_package_spec("odf-operator@latest");
+        assert!(res.err().unwrap().to_string().contains("(version latest)"));
+    }
+}
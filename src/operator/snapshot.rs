@@ -0,0 +1,205 @@
+use crate::cli::config::{ArchState, SnapshotState};
+use chrono::{DateTime, Duration, Utc};
+use custom_logger::*;
+use mirror_error::MirrorError;
+use std::fs;
+use std::path::Path;
+
+/// snapshot retention settings
+#[derive(Debug, Clone, PartialEq)]
+pub struct Retention {
+    // number of snapshots to keep per architecture (0 disables the history)
+    pub keep: usize,
+    // remove snapshots archived more than max_age_days ago
+    pub max_age_days: Option<i64>,
+}
+
+impl Default for Retention {
+    fn default() -> Self {
+        Self {
+            keep: 3,
+            max_age_days: None,
+        }
+    }
+}
+
+/// build the snapshot id from the time and the manifest digest i.e 20250101T120000Z-4f2a6c1b9e0d
+pub fn snapshot_id(digest: Option<&str>, time: &DateTime<Utc>) -> String {
+    let short = digest
+        .map(|d| d.trim_start_matches("sha256:").chars().take(12).collect())
+        .unwrap_or("unknown".to_string());
+    format!("{}-{}", time.format("%Y%m%dT%H%M%SZ"), short)
+}
+
+/// move the untarred cache of an architecture to <arch>/snapshots/<id>
+// the configs dir registered in the state file is rewritten to the new location
+pub fn archive_cache(cache_dir: &str, current: &ArchState) -> Result<SnapshotState, MirrorError> {
+    let cache = fs::canonicalize(cache_dir)
+        .map_err(|e| MirrorError::new(&format!("reading cache {} {}", cache_dir, e)))?
+        .to_string_lossy()
+        .to_string();
+    let rest = current
+        .configs_dir
+        .strip_prefix(&cache)
+        .ok_or(MirrorError::new(&format!(
+            "configs dir {} is not in cache {}",
+            current.configs_dir, cache
+        )))?;
+    let snapshots_dir = Path::new(&cache)
+        .parent()
+        .ok_or(MirrorError::new(&format!("cache {} has no parent", cache)))?
+        .join("snapshots");
+    fs::create_dir_all(&snapshots_dir)
+        .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", snapshots_dir, e)))?;
+    let archived = Utc::now();
+    let id = snapshot_id(
+        current.digest.as_deref(),
+        &current.last_update.unwrap_or(archived),
+    );
+    let path = snapshots_dir.join(&id).to_string_lossy().to_string();
+    if Path::new(&path).exists() {
+        return Err(MirrorError::new(&format!(
+            "snapshot {} already exists",
+            path
+        )));
+    }
+    fs::rename(&cache, &path)
+        .map_err(|e| MirrorError::new(&format!("moving {} to {} {}", cache, path, e)))?;
+    Ok(SnapshotState {
+        id,
+        digest: current.digest.clone(),
+        configs_dir: format!("{}{}", path, rest),
+        path,
+        last_update: current.last_update,
+        archived,
+    })
+}
+
+/// apply the retention settings (snapshots are ordered newest first)
+// returns the snapshots that were dropped, the caller removes their directories
+pub fn prune_snapshots(
+    snapshots: &mut Vec<SnapshotState>,
+    retention: &Retention,
+    now: DateTime<Utc>,
+) -> Vec<SnapshotState> {
+    let mut removed: Vec<SnapshotState> = vec![];
+    let mut kept: Vec<SnapshotState> = vec![];
+    for snapshot in snapshots.drain(..) {
+        let expired = retention
+            .max_age_days
+            .map(|days| snapshot.archived < now - Duration::days(days))
+            .unwrap_or(false);
+        if expired || kept.len() >= retention.keep {
+            removed.push(snapshot);
+        } else {
+            kept.push(snapshot);
+        }
+    }
+    *snapshots = kept;
+    removed
+}
+
+/// remove the directories of pruned snapshots
+pub fn remove_snapshots(removed: &[SnapshotState]) {
+    for snapshot in removed.iter() {
+        info!("[remove_snapshots] pruning snapshot {}", snapshot.id);
+        if Path::new(&snapshot.path).exists() {
+            if let Err(err) = rm_rf::remove(&snapshot.path) {
+                warn!(
+                    "[remove_snapshots] unable to delete {} {:?}",
+                    snapshot.path, err
+                );
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    fn snapshot(id: &str, archived: DateTime<Utc>) -> SnapshotState {
+        SnapshotState {
+            id: id.to_string(),
+            digest: None,
+            path: format!("/tmp/snapshots/{}", id),
+            configs_dir: format!("/tmp/snapshots/{}/configs/", id),
+            last_update: None,
+            archived,
+        }
+    }
+
+    #[test]
+    fn snapshot_id_pass() {
+        let time = DateTime::parse_from_rfc3339("2025-01-02T03:04:05Z")
+            .unwrap()
+            .with_timezone(&Utc);
+        assert_eq!(
+            snapshot_id(Some("sha256:4f2a6c1b9e0d1234"), &time),
+            "20250102T030405Z-4f2a6c1b9e0d"
+        );
+        assert_eq!(snapshot_id(None, &time), "20250102T030405Z-unknown");
+    }
+
+    #[test]
+    fn prune_snapshots_pass() {
+        let now = Utc::now();
+        let mut snapshots = vec![
+            snapshot("c", now - Duration::days(1)),
+            snapshot("b", now - Duration::days(10)),
+            snapshot("a", now - Duration::days(40)),
+        ];
+        let removed = prune_snapshots(
+            &mut snapshots,
+            &Retention {
+                keep: 5,
+                max_age_days: Some(30),
+            },
+            now,
+        );
+        assert_eq!(removed.len(), 1);
+        assert_eq!(removed[0].id, "a");
+        let removed = prune_snapshots(&mut snapshots, &Retention::default(), now);
+        assert!(removed.is_empty());
+        let removed = prune_snapshots(
+            &mut snapshots,
+            &Retention {
+                keep: 1,
+                max_age_days: None,
+            },
+            now,
+        );
+        assert_eq!(removed[0].id, "b");
+        assert_eq!(snapshots.len(), 1);
+        assert_eq!(snapshots[0].id, "c");
+    }
+
+    #[test]
+    fn archive_cache_pass() {
+        let base = "test-artifacts/snapshot-test/index/v1.0/amd64";
+        fs::create_dir_all(format!("{}/cache/hash/configs/albo-operator", base))
+            .expect("should create cache");
+        let cache = fs::canonicalize(format!("{}/cache", base))
+            .unwrap()
+            .to_string_lossy()
+            .to_string();
+        let current = ArchState {
+            digest: Some("sha256:4f2a6c1b9e0d1234".to_string()),
+            configs_dir: format!("{}/hash/configs/", cache),
+            last_update: None,
+            snapshots: vec![],
+        };
+        let res = archive_cache(&format!("{}/cache", base), &current).unwrap();
+        assert!(!Path::new(&format!("{}/cache", base)).exists());
+        assert!(res.id.ends_with("-4f2a6c1b9e0d"));
+        assert!(res
+            .configs_dir
+            .ends_with(&format!("/snapshots/{}/hash/configs/", res.id)));
+        assert!(Path::new(&res.configs_dir).join("albo-operator").exists());
+        remove_snapshots(std::slice::from_ref(&res));
+        assert!(!Path::new(&res.path).exists());
+        fs::remove_dir_all("test-artifacts/snapshot-test")
+            .expect("should delete snapshot test folder");
+    }
+}
@@ -0,0 +1,404 @@
+use crate::operator::local::write_fbc_packages;
+use custom_logger::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use rusqlite::{params, Connection, OpenFlags};
+use serde_json::{json, Value};
+use std::collections::BTreeMap;
+use std::path::Path;
+use walkdir::WalkDir;
+
+// directory (in the untarred cache) for the configs converted from index.db
+const MIGRATED_DIR: &str = "migrated-configs/configs";
+
+fn sql_error(e: rusqlite::Error) -> MirrorError {
+    MirrorError::new(&format!("reading index.db {}", e))
+}
+
+/// check if a table exists (older databases don't have the properties and dependencies tables)
+fn has_table(conn: &Connection, table: &str) -> Result<bool, MirrorError> {
+    conn.query_row(
+        "SELECT count(*) FROM sqlite_master WHERE type = 'table' AND name = ?1",
+        params![table],
+        |row| row.get::<_, i64>(0),
+    )
+    .map(|count| count > 0)
+    .map_err(sql_error)
+}
+
+/// run a query with a single parameter and collect the rows
+fn query_rows<T, F>(conn: &Connection, sql: &str, param: &str, f: F) -> Result<Vec<T>, MirrorError>
+where
+    F: FnMut(&rusqlite::Row<'_>) -> rusqlite::Result<T>,
+{
+    let mut stmt = conn.prepare(sql).map_err(sql_error)?;
+    let rows = stmt.query_map(params![param], f).map_err(sql_error)?;
+    rows.collect::<rusqlite::Result<Vec<T>>>()
+        .map_err(sql_error)
+}
+
+/// add a property (once) to the properties of a bundle
+fn push_property(properties: &mut Vec<Value>, type_prop: &str, value: Value) {
+    let property = json!({"type": type_prop, "value": value});
+    if !properties.contains(&property) {
+        properties.push(property);
+    }
+}
+
+/// get the properties of a bundle as declarative config properties
+// the properties table is used as is, olm.package and olm.gvk are added from the bundle version
+// and api_provider when missing (databases built before properties existed), the dependencies
+// table (or api_requirer) gives olm.package.required and olm.gvk.required
+fn bundle_properties(
+    conn: &Connection,
+    tables: &(bool, bool),
+    package: &str,
+    bundle: &str,
+    version: &str,
+) -> Result<Vec<Value>, MirrorError> {
+    let mut properties: Vec<Value> = vec![];
+    if tables.0 {
+        for (type_prop, value) in query_rows(
+            conn,
+            "SELECT type, value FROM properties WHERE operatorbundle_name = ?1",
+            bundle,
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )? {
+            if let Ok(value) = serde_json::from_str::<Value>(&value) {
+                push_property(&mut properties, &type_prop, value);
+            }
+        }
+    }
+    let has =
+        |properties: &[Value], type_prop: &str| properties.iter().any(|p| p["type"] == type_prop);
+    if !has(&properties, "olm.package") {
+        push_property(
+            &mut properties,
+            "olm.package",
+            json!({"packageName": package, "version": version}),
+        );
+    }
+    let gvks = |sql: &str| {
+        query_rows(conn, sql, bundle, |row| {
+            Ok(json!({
+                "group": row.get::<_, String>(0)?,
+                "version": row.get::<_, String>(1)?,
+                "kind": row.get::<_, String>(2)?,
+            }))
+        })
+    };
+    if !has(&properties, "olm.gvk") {
+        for gvk in gvks(
+            "SELECT DISTINCT a.group_name, a.version, a.kind FROM api_provider a
+             JOIN channel_entry c ON c.entry_id = a.channel_entry_id
+             WHERE c.operatorbundle_name = ?1 ORDER BY a.group_name, a.version, a.kind",
+        )? {
+            push_property(&mut properties, "olm.gvk", gvk);
+        }
+    }
+    let mut required_gvk = false;
+    if tables.1 {
+        for (type_dep, value) in query_rows(
+            conn,
+            "SELECT type, value FROM dependencies WHERE operatorbundle_name = ?1",
+            bundle,
+            |row| Ok((row.get::<_, String>(0)?, row.get::<_, String>(1)?)),
+        )? {
+            let value = match serde_json::from_str::<Value>(&value) {
+                Ok(value) => value,
+                Err(_) => continue,
+            };
+            match type_dep.as_str() {
+                "olm.package" => push_property(
+                    &mut properties,
+                    "olm.package.required",
+                    json!({"packageName": value["packageName"], "versionRange": value["version"]}),
+                ),
+                "olm.gvk" => {
+                    required_gvk = true;
+                    push_property(&mut properties, "olm.gvk.required", value);
+                }
+                _ => debug!(
+                    "[bundle_properties] skipping {} dependency of {}",
+                    type_dep, bundle
+                ),
+            }
+        }
+    }
+    if !required_gvk {
+        for gvk in gvks(
+            "SELECT DISTINCT a.group_name, a.version, a.kind FROM api_requirer a
+             JOIN channel_entry c ON c.entry_id = a.channel_entry_id
+             WHERE c.operatorbundle_name = ?1 ORDER BY a.group_name, a.version, a.kind",
+        )? {
+            push_property(&mut properties, "olm.gvk.required", gvk);
+        }
+    }
+    Ok(properties)
+}
+
+/// convert the packages, channels and bundles of a legacy sqlite catalog (index.db) to fbc objects
+// returns the objects of every package (olm.package, olm.channel and olm.bundle) as 'opm migrate' does
+pub fn read_sqlite_catalog(db: &Path) -> Result<BTreeMap<String, Vec<Value>>, MirrorError> {
+    let conn = Connection::open_with_flags(db, OpenFlags::SQLITE_OPEN_READ_ONLY)
+        .map_err(|e| MirrorError::new(&format!("opening {:?} {}", db, e)))?;
+    let tables = (
+        has_table(&conn, "properties")?,
+        has_table(&conn, "dependencies")?,
+    );
+    let mut packages: BTreeMap<String, Vec<Value>> = BTreeMap::new();
+
+    let mut stmt = conn
+        .prepare("SELECT name, default_channel FROM package ORDER BY name")
+        .map_err(sql_error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((row.get::<_, String>(0)?, row.get::<_, Option<String>>(1)?))
+        })
+        .map_err(sql_error)?;
+    for row in rows {
+        let (name, default_channel) = row.map_err(sql_error)?;
+        let mut package = json!({"schema": "olm.package", "name": name});
+        if let Some(channel) = default_channel.filter(|c| !c.is_empty()) {
+            package["defaultChannel"] = json!(channel);
+        }
+        packages.insert(name, vec![package]);
+    }
+
+    // channel_entry has a row per bundle and per replaced bundle, the oldest entries first
+    let mut stmt = conn
+        .prepare(
+            "SELECT c.package_name, c.channel_name, b.name, b.replaces, b.skips, b.skiprange
+             FROM channel_entry c JOIN operatorbundle b ON b.name = c.operatorbundle_name
+             GROUP BY c.package_name, c.channel_name, b.name
+             ORDER BY c.package_name, c.channel_name, max(c.depth) DESC, b.name",
+        )
+        .map_err(sql_error)?;
+    let rows = stmt
+        .query_map([], |row| {
+            Ok((
+                row.get::<_, String>(0)?,
+                row.get::<_, String>(1)?,
+                row.get::<_, String>(2)?,
+                row.get::<_, Option<String>>(3)?,
+                row.get::<_, Option<String>>(4)?,
+                row.get::<_, Option<String>>(5)?,
+            ))
+        })
+        .map_err(sql_error)?;
+    let mut channels: BTreeMap<(String, String), Vec<Value>> = BTreeMap::new();
+    let mut bundles: BTreeMap<String, String> = BTreeMap::new();
+    for row in rows {
+        let (package, channel, bundle, replaces, skips, skip_range) = row.map_err(sql_error)?;
+        let mut entry = json!({"name": bundle});
+        if let Some(replaces) = replaces.filter(|r| !r.is_empty()) {
+            entry["replaces"] = json!(replaces);
+        }
+        let skips = skips
+            .unwrap_or_default()
+            .split(',')
+            .map(|s| s.trim().to_string())
+            .filter(|s| !s.is_empty())
+            .collect::<Vec<String>>();
+        if !skips.is_empty() {
+            entry["skips"] = json!(skips);
+        }
+        if let Some(range) = skip_range.filter(|r| !r.is_empty()) {
+            entry["skipRange"] = json!(range);
+        }
+        bundles.insert(bundle, package.clone());
+        channels.entry((package, channel)).or_default().push(entry);
+    }
+    for ((package, channel), entries) in channels.into_iter() {
+        packages.entry(package.clone()).or_default().push(json!({
+            "schema": "olm.channel",
+            "package": package,
+            "name": channel,
+            "entries": entries,
+        }));
+    }
+
+    for (bundle, package) in bundles.iter() {
+        let (image, version) = conn
+            .query_row(
+                "SELECT bundlepath, version FROM operatorbundle WHERE name = ?1",
+                params![bundle],
+                |row| {
+                    Ok((
+                        row.get::<_, Option<String>>(0)?,
+                        row.get::<_, Option<String>>(1)?,
+                    ))
+                },
+            )
+            .map_err(sql_error)?;
+        let related_images = query_rows(
+            &conn,
+            "SELECT DISTINCT image FROM related_image WHERE operatorbundle_name = ?1 ORDER BY image",
+            bundle,
+            |row| Ok(json!({"name": "", "image": row.get::<_, String>(0)?})),
+        )?;
+        let properties = bundle_properties(
+            &conn,
+            &tables,
+            package,
+            bundle,
+            &version.unwrap_or_default(),
+        )?;
+        packages.entry(package.clone()).or_default().push(json!({
+            "schema": "olm.bundle",
+            "name": bundle,
+            "package": package,
+            "image": image.unwrap_or_default(),
+            "properties": properties,
+            "relatedImages": related_images,
+        }));
+    }
+    Ok(packages)
+}
+
+/// convert the index.db of an untarred catalog (4.10 and before) to declarative configs
+// the configs are written to <cache>/migrated-configs/configs and the updated configs are built (only
+// when missing or the cache changed), returns None when the cache has no index.db
+pub fn migrate_sqlite_cache(
+    cache_dir: &str,
+    is_changed: bool,
+) -> Result<Option<String>, MirrorError> {
+    let db = WalkDir::new(cache_dir)
+        .into_iter()
+        .filter_map(|e| e.ok())
+        .find(|e| e.file_type().is_file() && e.file_name() == "index.db");
+    let db = match db {
+        Some(db) => db.into_path(),
+        None => return Ok(None),
+    };
+    let configs_dir = Path::new(cache_dir).join(MIGRATED_DIR);
+    if is_changed || !configs_dir.exists() {
+        info!("[migrate_sqlite_cache] converting {:?}", db);
+        let packages = read_sqlite_catalog(&db)?;
+        write_fbc_packages(&configs_dir, &packages)?;
+        DeclarativeConfig::build_updated_configs(configs_dir.to_string_lossy().to_string())?;
+        info!(
+            "[migrate_sqlite_cache] converted {} packages to {:?}",
+            packages.len(),
+            configs_dir
+        );
+    }
+    Ok(Some(configs_dir.to_string_lossy().to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::load_package_fbc;
+    use std::fs;
+
+    // subset of the operator-registry schema with a package in two channels
+    fn create_index_db(path: &Path, with_properties: bool) {
+        let conn = Connection::open(path).unwrap();
+        conn.execute_batch(
+            "CREATE TABLE package (name TEXT PRIMARY KEY, default_channel TEXT);
+             CREATE TABLE channel (name TEXT, package_name TEXT, head_operatorbundle_name TEXT);
+             CREATE TABLE operatorbundle (name TEXT PRIMARY KEY, csv TEXT, bundle TEXT, bundlepath TEXT,
+                 version TEXT, skiprange TEXT, replaces TEXT, skips TEXT);
+             CREATE TABLE channel_entry (entry_id INTEGER PRIMARY KEY, channel_name TEXT, package_name TEXT,
+                 operatorbundle_name TEXT, replaces INTEGER, depth INTEGER);
+             CREATE TABLE api_provider (group_name TEXT, version TEXT, kind TEXT, channel_entry_id INTEGER);
+             CREATE TABLE api_requirer (group_name TEXT, version TEXT, kind TEXT, channel_entry_id INTEGER);
+             CREATE TABLE related_image (image TEXT, operatorbundle_name TEXT);
+             INSERT INTO package VALUES ('albo-operator', 'stable-v1');
+             INSERT INTO operatorbundle VALUES ('albo-operator.v1.0.0', '', '', 'registry.example.com/albo/bundle@sha256:1000',
+                 '1.0.0', '', '', '');
+             INSERT INTO operatorbundle VALUES ('albo-operator.v1.1.0', '', '', 'registry.example.com/albo/bundle@sha256:1100',
+                 '1.1.0', '>=0.9.0 <1.1.0', 'albo-operator.v1.0.0', 'albo-operator.v0.9.0,albo-operator.v0.9.1');
+             INSERT INTO channel_entry VALUES (1, 'stable-v1', 'albo-operator', 'albo-operator.v1.1.0', 2, 0);
+             INSERT INTO channel_entry VALUES (2, 'stable-v1', 'albo-operator', 'albo-operator.v1.0.0', NULL, 1);
+             INSERT INTO channel_entry VALUES (3, 'stable-v0', 'albo-operator', 'albo-operator.v1.0.0', NULL, 0);
+             INSERT INTO api_provider VALUES ('networking.example.com', 'v1', 'TargetGroupBinding', 1);
+             INSERT INTO api_requirer VALUES ('monitoring.example.com', 'v1', 'ServiceMonitor', 1);
+             INSERT INTO related_image VALUES ('registry.example.com/albo/controller@sha256:1100', 'albo-operator.v1.1.0');",
+        )
+        .unwrap();
+        if with_properties {
+            conn.execute_batch(
+                "CREATE TABLE properties (type TEXT, value TEXT, operatorbundle_name TEXT,
+                     operatorbundle_version TEXT, operatorbundle_path TEXT);
+                 CREATE TABLE dependencies (type TEXT, value TEXT, operatorbundle_name TEXT,
+                     operatorbundle_version TEXT, operatorbundle_path TEXT);
+                 INSERT INTO properties VALUES ('olm.package', '{\"packageName\":\"albo-operator\",\"version\":\"1.1.0\"}',
+                     'albo-operator.v1.1.0', '1.1.0', '');
+                 INSERT INTO dependencies VALUES ('olm.package', '{\"packageName\":\"cert-manager-operator\",\"version\":\">=1.10.0\"}',
+                     'albo-operator.v1.1.0', '1.1.0', '');",
+            )
+            .unwrap();
+        }
+    }
+
+    #[test]
+    fn read_sqlite_catalog_pass() {
+        let base = Path::new("test-artifacts/sqlite-test");
+        fs::create_dir_all(base).unwrap();
+        let db = base.join("index.db");
+        create_index_db(&db, true);
+        let packages = read_sqlite_catalog(&db).unwrap();
+        let objects = &packages["albo-operator"];
+        assert_eq!(objects[0]["defaultChannel"], "stable-v1");
+        assert_eq!(objects[1]["name"], "stable-v0");
+        let entries = &objects[2]["entries"];
+        assert_eq!(entries[0]["name"], "albo-operator.v1.0.0");
+        assert_eq!(entries[1]["replaces"], "albo-operator.v1.0.0");
+        assert_eq!(entries[1]["skips"][1], "albo-operator.v0.9.1");
+        assert_eq!(entries[1]["skipRange"], ">=0.9.0 <1.1.0");
+        let bundle = objects
+            .iter()
+            .find(|o| o["name"] == "albo-operator.v1.1.0")
+            .unwrap();
+        assert_eq!(
+            bundle["image"],
+            "registry.example.com/albo/bundle@sha256:1100"
+        );
+        let types = bundle["properties"]
+            .as_array()
+            .unwrap()
+            .iter()
+            .map(|p| p["type"].as_str().unwrap())
+            .collect::<Vec<&str>>();
+        assert_eq!(
+            types,
+            vec![
+                "olm.package",
+                "olm.gvk",
+                "olm.package.required",
+                "olm.gvk.required"
+            ]
+        );
+        assert_eq!(bundle["properties"][2]["value"]["versionRange"], ">=1.10.0");
+        assert_eq!(
+            bundle["relatedImages"][0]["image"],
+            "registry.example.com/albo/controller@sha256:1100"
+        );
+        fs::remove_dir_all(base).expect("should delete sqlite test folder");
+    }
+
+    #[test]
+    fn migrate_sqlite_cache_pass() {
+        let base = "test-artifacts/sqlite-cache-test";
+        fs::create_dir_all(format!("{}/database", base)).unwrap();
+        assert_eq!(migrate_sqlite_cache(base, false).unwrap(), None);
+        create_index_db(&Path::new(base).join("database/index.db"), false);
+        let configs_dir = migrate_sqlite_cache(base, false).unwrap().unwrap();
+        assert!(configs_dir.ends_with("migrated-configs/configs"));
+        let configs = load_package_fbc(&configs_dir, "albo-operator").unwrap();
+        assert_eq!(configs.len(), 5);
+        // olm.package is built from the bundle version when the properties table is missing
+        let bundle = configs
+            .iter()
+            .find(|dc| dc.name == "albo-operator.v1.0.0")
+            .unwrap();
+        assert_eq!(
+            bundle.properties.as_ref().unwrap()[0].type_prop,
+            "olm.package"
+        );
+        fs::remove_dir_all(base).expect("should delete sqlite test folder");
+    }
+}
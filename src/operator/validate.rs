@@ -0,0 +1,257 @@
+use crate::operator::bundle::VersionRange;
+use crate::operator::graph::get_upgrade_targets;
+use mirror_catalog::DeclarativeConfig;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// severity of a validation issue (errors make 'validate' exit with a non-zero code)
+#[derive(Serialize, Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[serde(rename_all = "lowercase")]
+pub enum Severity {
+    Error,
+    Warning,
+}
+
+/// problem found in the declarative config of a package
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Issue {
+    pub severity: Severity,
+    // short identifier of the check i.e dangling-replaces
+    pub check: String,
+    pub package: String,
+    // schema=name of the object with the problem
+    pub object: String,
+    pub message: String,
+}
+
+/// validate the fbc objects of a package
+// configs must be the objects as they are in the catalog (duplicates are not merged)
+pub fn validate_package(package: &str, configs: &[DeclarativeConfig]) -> Vec<Issue> {
+    let mut issues: Vec<Issue> = vec![];
+    let mut issue = |severity: Severity, check: &str, dc: &DeclarativeConfig, message: String| {
+        issues.push(Issue {
+            severity,
+            check: check.to_string(),
+            package: package.to_string(),
+            object: format!("{}={}", dc.name, dc.schema),
+            message,
+        })
+    };
+
+    let mut bundles: BTreeMap<&str, usize> = BTreeMap::new();
+    for dc in configs.iter().filter(|dc| dc.schema == "olm.bundle") {
+        *bundles.entry(dc.name.as_str()).or_default() += 1;
+        if bundles[dc.name.as_str()] == 2 {
+            issue(
+                Severity::Error,
+                "duplicate-bundle",
+                dc,
+                format!("bundle {} is defined more than once", dc.name),
+            );
+        }
+        if dc.image.as_deref().unwrap_or_default().trim().is_empty() {
+            issue(
+                Severity::Error,
+                "bundle-without-image",
+                dc,
+                format!("bundle {} has no image", dc.name),
+            );
+        }
+    }
+
+    let channels = configs
+        .iter()
+        .filter(|dc| dc.schema == "olm.channel")
+        .collect::<Vec<&DeclarativeConfig>>();
+    let dc_map = configs
+        .iter()
+        .map(|dc| (format!("{}={}", dc.name, dc.schema), dc.clone()))
+        .collect::<HashMap<String, DeclarativeConfig>>();
+    let mut in_channel: BTreeSet<&str> = BTreeSet::new();
+    for dc in channels.iter() {
+        let entries = dc.entries.as_deref().unwrap_or_default();
+        let names = entries
+            .iter()
+            .map(|e| e.name.as_str())
+            .collect::<BTreeSet<&str>>();
+        // only the head has no upgrade target in an unbroken channel
+        let broken = entries
+            .iter()
+            .filter(|e| get_upgrade_targets(&dc_map, entries, &e.name).is_empty())
+            .count()
+            > 1;
+        for entry in entries.iter() {
+            in_channel.insert(entry.name.as_str());
+            if !bundles.contains_key(entry.name.as_str()) {
+                issue(
+                    Severity::Error,
+                    "missing-bundle",
+                    dc,
+                    format!(
+                        "entry {} references a bundle that does not exist",
+                        entry.name
+                    ),
+                );
+            }
+            // a replaces outside of the channel ends the chain (as in opm), it's only an error
+            // when the channel is broken, a tail that replaces a pruned bundle is a warning and
+            // a tail that replaces a bundle of another channel is valid
+            if let Some(replaces) = entry.replaces.as_ref() {
+                if !names.contains(replaces.as_str()) {
+                    if broken {
+                        issue(
+                            Severity::Error,
+                            "dangling-replaces",
+                            dc,
+                            format!(
+                                "entry {} replaces {} which is not in the channel",
+                                entry.name, replaces
+                            ),
+                        );
+                    } else if !bundles.contains_key(replaces.as_str()) {
+                        issue(
+                            Severity::Warning,
+                            "dangling-replaces",
+                            dc,
+                            format!(
+                                "entry {} replaces {} which is not in the catalog (pruned tail)",
+                                entry.name, replaces
+                            ),
+                        );
+                    }
+                }
+            }
+            // skipped bundles are often pruned from the catalog, so this is only a warning
+            for skip in entry.skips.clone().unwrap_or_default().iter() {
+                if !names.contains(skip.as_str()) && !bundles.contains_key(skip.as_str()) {
+                    issue(
+                        Severity::Warning,
+                        "dangling-skips",
+                        dc,
+                        format!(
+                            "entry {} skips {} which is not in the catalog",
+                            entry.name, skip
+                        ),
+                    );
+                }
+            }
+            if let Some(range) = entry.skip_range.as_ref() {
+                if VersionRange::parse(range).is_none() {
+                    issue(
+                        Severity::Error,
+                        "invalid-skip-range",
+                        dc,
+                        format!(
+                            "entry {} has an unparsable skipRange \"{}\"",
+                            entry.name, range
+                        ),
+                    );
+                }
+            }
+        }
+    }
+
+    match configs.iter().find(|dc| dc.schema == "olm.package") {
+        Some(dc) => {
+            if let Some(default_channel) = dc.default_channel.as_ref() {
+                if !channels.iter().any(|c| &c.name == default_channel) {
+                    issue(
+                        Severity::Error,
+                        "missing-default-channel",
+                        dc,
+                        format!("default channel {} does not exist", default_channel),
+                    );
+                }
+            }
+        }
+        None => issues.push(Issue {
+            severity: Severity::Error,
+            check: "missing-package".to_string(),
+            package: package.to_string(),
+            object: format!("{}=olm.package", package),
+            message: "package has no olm.package object".to_string(),
+        }),
+    }
+
+    // duplicates are reported only once
+    let mut orphans: BTreeSet<&str> = BTreeSet::new();
+    for dc in configs.iter().filter(|dc| dc.schema == "olm.bundle") {
+        if !in_channel.contains(dc.name.as_str()) && orphans.insert(dc.name.as_str()) {
+            issues.push(Issue {
+                severity: Severity::Warning,
+                check: "orphaned-bundle".to_string(),
+                package: package.to_string(),
+                object: format!("{}={}", dc.name, dc.schema),
+                message: format!("bundle {} is not in any channel", dc.name),
+            });
+        }
+    }
+    issues
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::load_package_fbc;
+
+    #[test]
+    fn validate_package_pass() {
+        let configs = load_package_fbc("test-artifacts/fbc-configs/", "albo-operator").unwrap();
+        // the tail of stable-v0 replaces a bundle pruned from the catalog
+        let res = validate_package("albo-operator", &configs);
+        assert_eq!(res.len(), 1);
+        assert_eq!(res[0].severity, Severity::Warning);
+        assert_eq!(res[0].check, "dangling-replaces");
+        assert_eq!(res[0].object, "stable-v0=olm.channel");
+        assert!(res[0].message.contains("replaces albo-operator.v0.8.0"));
+    }
+
+    #[test]
+    fn validate_package_cross_channel_pass() {
+        // the tail of stable-4.15 replaces the head of stable-4.14 (new channels of an index)
+        let configs =
+            load_package_fbc("test-artifacts/fbc-configs/", "cross-channel-operator").unwrap();
+        assert_eq!(validate_package("cross-channel-operator", &configs), vec![]);
+    }
+
+    #[test]
+    fn validate_package_fail() {
+        let configs = load_package_fbc("test-artifacts/fbc-configs/", "broken-operator").unwrap();
+        let res = validate_package("broken-operator", &configs);
+        let checks = res
+            .iter()
+            .map(|i| (i.severity.clone(), i.check.as_str(), i.object.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            checks,
+            vec![
+                (
+                    Severity::Error,
+                    "duplicate-bundle",
+                    "broken-operator.v0.2.0=olm.bundle"
+                ),
+                (
+                    Severity::Error,
+                    "bundle-without-image",
+                    "broken-operator.v0.9.0=olm.bundle"
+                ),
+                (Severity::Error, "dangling-replaces", "alpha=olm.channel"),
+                (Severity::Warning, "dangling-skips", "alpha=olm.channel"),
+                (Severity::Error, "invalid-skip-range", "alpha=olm.channel"),
+                (Severity::Error, "missing-bundle", "alpha=olm.channel"),
+                (
+                    Severity::Error,
+                    "missing-default-channel",
+                    "broken-operator=olm.package"
+                ),
+                (
+                    Severity::Warning,
+                    "orphaned-bundle",
+                    "broken-operator.v0.9.0=olm.bundle"
+                ),
+            ]
+        );
+        assert!(res[2].message.contains("replaces broken-operator.v0.0.9"));
+    }
+}
@@ -0,0 +1,256 @@
+use crate::operator::bundle::*;
+use crate::operator::catalog::*;
+use crate::query::output::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::HashMap;
+
+/// related image of a bundle
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BundleImage {
+    pub name: String,
+    pub image: String,
+}
+
+/// full details of an olm.bundle used by 'show bundle'
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct BundleDetails {
+    pub name: String,
+    pub package: String,
+    pub version: Option<String>,
+    pub image: Option<String>,
+    pub channels: Vec<String>,
+    pub provided_gvks: Vec<Gvk>,
+    pub required_gvks: Vec<Gvk>,
+    pub required_packages: Vec<RequiredPackage>,
+    pub related_images: Vec<BundleImage>,
+    pub csv_metadata: Option<serde_json::Value>,
+    pub properties: Vec<serde_json::Value>,
+}
+
+/// collect the details of a bundle from the package declarative config map
+// the properties are read from the olm.bundle object in <configs_dir>/<package>/ (every field is
+// kept), the declarative config is only used when the original files can't be read
+pub fn get_bundle_details(
+    package: &str,
+    bundle: &str,
+    dc_map: &HashMap<String, DeclarativeConfig>,
+    configs_dir: &str,
+) -> Result<BundleDetails, MirrorError> {
+    let dc = match dc_map.get(&format!("{}=olm.bundle", bundle)) {
+        Some(dc) => dc,
+        None => {
+            let bundles = get_bundles(dc_map)
+                .keys()
+                .map(|b| format!("  {}", b))
+                .collect::<Vec<String>>();
+            return Err(MirrorError::new(&format!(
+                "bundle \"{}\" not found in package \"{}\", valid bundles are :\n{}",
+                bundle,
+                package,
+                bundles.join("\n")
+            )));
+        }
+    };
+    let channels = get_channels(dc_map)
+        .iter()
+        .filter(|(_, entries)| entries.iter().any(|e| e.name == bundle))
+        .map(|(name, _)| name.clone())
+        .collect();
+    let related_images = dc
+        .related_images
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .map(|r| BundleImage {
+            name: r.name.clone(),
+            image: r.image.clone(),
+        })
+        .collect();
+    let properties = match load_bundle_value(configs_dir, package, bundle) {
+        Some(value) => value["properties"].as_array().cloned().unwrap_or_default(),
+        None => dc
+            .properties
+            .clone()
+            .unwrap_or_default()
+            .iter()
+            .filter_map(|p| serde_json::to_value(p).ok())
+            .collect(),
+    };
+    let csv_metadata = properties
+        .iter()
+        .find(|p| p["type"] == "olm.csv.metadata")
+        .map(|p| p["value"].clone());
+    Ok(BundleDetails {
+        name: bundle.to_string(),
+        package: package.to_string(),
+        version: get_bundle_version(dc_map, bundle).map(|v| v.to_string()),
+        image: dc.image.clone(),
+        channels,
+        provided_gvks: get_gvks(dc, "olm.gvk"),
+        required_gvks: get_gvks(dc, "olm.gvk.required"),
+        required_packages: get_required_packages(dc),
+        related_images,
+        csv_metadata,
+        properties,
+    })
+}
+
+/// format the bundle details as text, json or yaml
+pub fn format_bundle(
+    details: &BundleDetails,
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(details, format);
+    }
+    let mut lines = vec![
+        format!("name:     {}", details.name),
+        format!("package:  {}", details.package),
+        format!("version:  {}", details.version.clone().unwrap_or_default()),
+        format!("image:    {}", details.image.clone().unwrap_or_default()),
+        format!("channels: {}", details.channels.join(", ")),
+    ];
+    let mut section = |title: &str, items: Vec<String>| {
+        lines.push(format!("{}:", title));
+        if items.is_empty() {
+            lines.push("  (none)".to_string());
+        }
+        for item in items.iter() {
+            lines.push(format!("  {}", item));
+        }
+    };
+    section(
+        "provided gvks",
+        details
+            .provided_gvks
+            .iter()
+            .map(|g| g.to_string())
+            .collect(),
+    );
+    section(
+        "required gvks",
+        details
+            .required_gvks
+            .iter()
+            .map(|g| g.to_string())
+            .collect(),
+    );
+    section(
+        "required packages",
+        details
+            .required_packages
+            .iter()
+            .map(|p| match p.version_range.as_ref() {
+                Some(range) => format!("{} ({})", p.package_name, range),
+                None => p.package_name.clone(),
+            })
+            .collect(),
+    );
+    let rows = details
+        .related_images
+        .iter()
+        .map(|r| vec![r.name.clone(), r.image.clone()])
+        .collect::<Vec<Vec<String>>>();
+    section(
+        "related images",
+        format_table(&["name", "image"], &rows)
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+    );
+    let csv_metadata = match details.csv_metadata.as_ref() {
+        Some(value) => serde_yaml::to_string(value)
+            .unwrap_or_default()
+            .lines()
+            .map(|l| l.to_string())
+            .collect(),
+        None => vec![],
+    };
+    section("csv metadata", csv_metadata);
+    section(
+        "properties",
+        details
+            .properties
+            .iter()
+            .map(|p| format!("{} {}", p["type"].as_str().unwrap_or_default(), p["value"]))
+            .collect(),
+    );
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn get_bundle_details_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let res = get_bundle_details("albo-operator", "albo-operator.v1.0.0", &dc_map, "").unwrap();
+        assert_eq!(res.version, Some("1.0.0".to_string()));
+        assert_eq!(
+            res.channels,
+            vec!["stable-v0".to_string(), "stable-v1".to_string()]
+        );
+        assert_eq!(res.provided_gvks.len(), 1);
+        assert_eq!(res.related_images.len(), 2);
+        assert_eq!(res.properties.len(), 2);
+
+        let text = format_bundle(&res, &OutputFormat::Table).unwrap();
+        assert!(text.contains("networking.example.com/v1beta1/TargetGroupBinding"));
+        assert!(text.contains("registry.example.com/albo/controller@sha256:c100"));
+        let json = format_bundle(&res, &OutputFormat::Json).unwrap();
+        assert!(json.contains("\"providedGvks\""));
+    }
+
+    #[test]
+    fn get_bundle_details_raw_properties_pass() {
+        let configs_dir = "test-artifacts/fbc-configs/";
+        let dc_map = load_package_fbc(configs_dir, "albo-operator")
+            .unwrap()
+            .into_iter()
+            .map(|dc| (format!("{}={}", dc.name, dc.schema), dc))
+            .collect::<HashMap<String, DeclarativeConfig>>();
+        let res = get_bundle_details(
+            "albo-operator",
+            "albo-operator.v1.0.0",
+            &dc_map,
+            configs_dir,
+        )
+        .unwrap();
+        // properties are read from the catalog files, unknown types and fields are kept
+        assert_eq!(res.properties.len(), 4);
+        let csv_metadata = res.csv_metadata.clone().unwrap();
+        assert_eq!(csv_metadata["displayName"], "AWS Load Balancer Operator");
+        assert_eq!(csv_metadata["maturity"], "stable");
+        assert_eq!(res.properties[3]["type"], "example.com/support-tier");
+
+        let text = format_bundle(&res, &OutputFormat::Table).unwrap();
+        assert!(text.contains("csv metadata:\n  annotations:\n    capabilities: Basic Install"));
+        assert!(text.contains("  maturity: stable"));
+        assert!(text
+            .contains("  example.com/support-tier {\"tier\":\"gold\",\"until\":\"2027-01-01\"}"));
+        let json = format_bundle(&res, &OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["csvMetadata"]["provider"]["name"], "Example");
+        assert_eq!(value["csvMetadata"]["maturity"], "stable");
+        assert_eq!(value["properties"][3]["value"]["tier"], "gold");
+    }
+
+    #[test]
+    fn get_bundle_details_fail() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let res = get_bundle_details("albo-operator", "albo-operator.v9.9.9", &dc_map, "");
+        assert!(res.is_err());
+        assert!(res
+            .err()
+            .unwrap()
+            .to_string()
+            .contains("  albo-operator.v1.2.0"));
+    }
+}
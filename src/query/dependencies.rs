@@ -0,0 +1,96 @@
+use crate::operator::dependency::*;
+use crate::query::output::*;
+use mirror_error::MirrorError;
+
+/// draw the dependency tree (one line per requirement)
+// unsatisfiable requirements are marked with ✗, bundles already resolved higher up with (see above)
+pub fn dependency_lines(tree: &DependencyTree) -> Vec<String> {
+    fn walk(nodes: &[DependencyNode], prefix: &str, lines: &mut Vec<String>) {
+        for (i, node) in nodes.iter().enumerate() {
+            let last = i == nodes.len() - 1;
+            let kind = match node.kind {
+                RequirementKind::Package => "package",
+                RequirementKind::Gvk => "gvk",
+            };
+            let result = match (node.error.as_ref(), node.bundle.as_ref()) {
+                (Some(err), _) => format!("✗ {}", err),
+                (None, Some(bundle)) if node.repeated => format!("→ {} (see above)", bundle),
+                (None, Some(bundle)) => format!("→ {}", bundle),
+                (None, None) => String::new(),
+            };
+            lines.push(format!(
+                "{}{} {} {} {}",
+                prefix,
+                if last { "└──" } else { "├──" },
+                kind,
+                node.requirement,
+                result
+            ));
+            let prefix = format!("{}{}", prefix, if last { "    " } else { "│   " });
+            walk(&node.children, &prefix, lines);
+        }
+    }
+    let mut lines = vec![tree.bundle.clone()];
+    if tree.dependencies.is_empty() {
+        lines.push("└── (no dependencies)".to_string());
+    }
+    walk(&tree.dependencies, "", &mut lines);
+    lines
+}
+
+/// format the dependency tree as text, json or yaml
+pub fn format_dependencies(
+    tree: &DependencyTree,
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(tree, format);
+    }
+    let mut lines = dependency_lines(tree);
+    let unsatisfied = tree.unsatisfied();
+    if !unsatisfied.is_empty() {
+        lines.push(String::new());
+        lines.push(format!(
+            "unsatisfiable requirements ({}):",
+            unsatisfied.len()
+        ));
+        for (requirement, reason) in unsatisfied.iter() {
+            lines.push(format!("  {} ({})", requirement, reason));
+        }
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::catalog_index_from_files;
+
+    #[test]
+    fn format_dependencies_pass() {
+        let index = catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]);
+        let tree = resolve_dependencies(&index, "albo-operator", "albo-operator.v1.2.0").unwrap();
+        let res = format_dependencies(&tree, &OutputFormat::Table).unwrap();
+        let expected = "albo-operator.v1.2.0
+├── package cert-manager-operator >=1.12.0 <2.0.0 → cert-manager-operator.v1.12.0
+│   └── package monitoring-operator >=0.5.0 → monitoring-operator.v0.5.0
+│       ├── package cert-manager-operator >=1.11.0 → cert-manager-operator.v1.12.0 (see above)
+│       └── gvk logging.example.com/v1/LogForwarder ✗ no package in the catalog provides this gvk
+└── gvk monitoring.example.com/v1/ServiceMonitor → cert-manager-operator.v1.12.0 (see above)
+
+unsatisfiable requirements (1):
+  logging.example.com/v1/LogForwarder (no package in the catalog provides this gvk)";
+        assert_eq!(res, expected);
+
+        let tree = resolve_dependencies(&index, "albo-operator", "albo-operator.v1.0.0").unwrap();
+        assert_eq!(
+            dependency_lines(&tree),
+            vec!["albo-operator.v1.0.0", "└── (no dependencies)"]
+        );
+    }
+}
@@ -0,0 +1,85 @@
+use crate::operator::dependency::{CatalogIndex, RequirementKind};
+use crate::operator::dependents::*;
+use crate::query::output::*;
+use mirror_error::MirrorError;
+
+/// get the bundles of other packages that depend on the package
+pub fn query_dependents(
+    catalog: &CatalogIndex,
+    index: &ReverseIndex,
+    package: &str,
+) -> Result<Vec<Dependent>, MirrorError> {
+    if !catalog.packages.contains_key(package) {
+        return Err(MirrorError::new(&format!(
+            "package {} not found in catalog",
+            package
+        )));
+    }
+    Ok(index.get_dependents(package))
+}
+
+/// format the dependents as table, json or yaml
+// the breaks column shows what is left without the package (other providers of a gvk)
+pub fn format_dependents(
+    dependents: &[Dependent],
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(&dependents, format);
+    }
+    if dependents.is_empty() {
+        return Ok("no bundle in the catalog depends on this package".to_string());
+    }
+    let rows = dependents
+        .iter()
+        .map(|d| {
+            vec![
+                d.package.clone(),
+                d.bundle.clone(),
+                match d.kind {
+                    RequirementKind::Package => "package".to_string(),
+                    RequirementKind::Gvk => "gvk".to_string(),
+                },
+                d.requirement.clone(),
+                match d.breaks {
+                    true => "yes".to_string(),
+                    false => format!("no (also provided by {})", d.alternatives.join(", ")),
+                },
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    Ok(format_table(
+        &["package", "bundle", "type", "requires", "breaks"],
+        &rows,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::catalog_index_from_files;
+    use crate::operator::provides::GvkIndex;
+
+    #[test]
+    fn format_dependents_pass() {
+        let catalog = catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]);
+        let index = ReverseIndex::new(&catalog, &GvkIndex::new(&catalog));
+        let res = query_dependents(&catalog, &index, "monitoring-operator").unwrap();
+        let text = format_dependents(&res, &OutputFormat::Table).unwrap();
+        let expected = "PACKAGE                BUNDLE                         TYPE     REQUIRES                                  BREAKS
+albo-operator          albo-operator.v1.2.0           gvk      monitoring.example.com/v1/ServiceMonitor  no (also provided by cert-manager-operator)
+cert-manager-operator  cert-manager-operator.v1.12.0  package  monitoring-operator >=0.5.0               yes";
+        assert_eq!(text, expected);
+        let res = query_dependents(&catalog, &index, "albo-operator").unwrap();
+        assert_eq!(
+            format_dependents(&res, &OutputFormat::Table).unwrap(),
+            "no bundle in the catalog depends on this package"
+        );
+        assert!(query_dependents(&catalog, &index, "no-operator").is_err());
+    }
+}
@@ -0,0 +1,369 @@
+use crate::operator::catalog::*;
+use clap::ValueEnum;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::{BTreeMap, BTreeSet, HashMap};
+
+/// output format used by 'diff'
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum DiffFormat {
+    Text,
+    Json,
+    Markdown,
+}
+
+/// changed value (None when not set)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct Change {
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// changed related image of a bundle that is in both catalogs
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ImageChange {
+    pub bundle: String,
+    pub name: String,
+    pub from: Option<String>,
+    pub to: Option<String>,
+}
+
+/// changes of a package that is in both catalogs
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageDiff {
+    pub name: String,
+    #[serde(skip_serializing_if = "Option::is_none")]
+    pub default_channel: Option<Change>,
+    pub added_channels: Vec<String>,
+    pub removed_channels: Vec<String>,
+    pub added_bundles: Vec<String>,
+    pub removed_bundles: Vec<String>,
+    pub changed_images: Vec<ImageChange>,
+}
+
+/// differences between two catalogs used by 'diff'
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogDiff {
+    pub from: String,
+    pub to: String,
+    pub added_packages: Vec<String>,
+    pub removed_packages: Vec<String>,
+    pub changed_packages: Vec<PackageDiff>,
+}
+
+impl PackageDiff {
+    /// check if anything changed
+    pub fn is_empty(&self) -> bool {
+        self.default_channel.is_none()
+            && self.added_channels.is_empty()
+            && self.removed_channels.is_empty()
+            && self.added_bundles.is_empty()
+            && self.removed_bundles.is_empty()
+            && self.changed_images.is_empty()
+    }
+}
+
+/// get the entries of a that are not in b
+fn missing<T: Ord + Clone>(a: &BTreeSet<T>, b: &BTreeSet<T>) -> Vec<T> {
+    a.difference(b).cloned().collect()
+}
+
+/// get the related images of a bundle keyed by name
+// the bundle image itself usually has an empty name so the repository is used instead
+fn related_images(dc: &DeclarativeConfig) -> BTreeMap<String, String> {
+    dc.related_images
+        .clone()
+        .unwrap_or_default()
+        .iter()
+        .map(|r| {
+            let name = match r.name.is_empty() {
+                true => r.image.split('@').next().unwrap_or_default().to_string(),
+                false => r.name.clone(),
+            };
+            (name, r.image.clone())
+        })
+        .collect()
+}
+
+/// compare the declarative config maps of a package
+pub fn diff_package(
+    name: &str,
+    old: &HashMap<String, DeclarativeConfig>,
+    new: &HashMap<String, DeclarativeConfig>,
+) -> PackageDiff {
+    let old_default = get_default_channel(old);
+    let new_default = get_default_channel(new);
+    let default_channel = match old_default == new_default {
+        true => None,
+        false => Some(Change {
+            from: old_default,
+            to: new_default,
+        }),
+    };
+    let old_channels = get_channels(old).into_keys().collect::<BTreeSet<String>>();
+    let new_channels = get_channels(new).into_keys().collect::<BTreeSet<String>>();
+    let old_bundles = get_bundles(old);
+    let new_bundles = get_bundles(new);
+    let old_names = old_bundles.keys().cloned().collect::<BTreeSet<String>>();
+    let new_names = new_bundles.keys().cloned().collect::<BTreeSet<String>>();
+
+    let mut changed_images: Vec<ImageChange> = vec![];
+    for (bundle, old_dc) in old_bundles.iter() {
+        let new_dc = match new_bundles.get(bundle) {
+            Some(dc) => dc,
+            None => continue,
+        };
+        let old_images = related_images(old_dc);
+        let new_images = related_images(new_dc);
+        let names = old_images
+            .keys()
+            .chain(new_images.keys())
+            .collect::<BTreeSet<&String>>();
+        for image in names {
+            let from = old_images.get(image).cloned();
+            let to = new_images.get(image).cloned();
+            if from != to {
+                changed_images.push(ImageChange {
+                    bundle: bundle.clone(),
+                    name: image.clone(),
+                    from,
+                    to,
+                });
+            }
+        }
+    }
+    PackageDiff {
+        name: name.to_string(),
+        default_channel,
+        added_channels: missing(&new_channels, &old_channels),
+        removed_channels: missing(&old_channels, &new_channels),
+        added_bundles: missing(&new_names, &old_names),
+        removed_bundles: missing(&old_names, &new_names),
+        changed_images,
+    }
+}
+
+/// compare two catalogs (configs dirs as registered in the state file)
+pub fn query_diff(from: (&str, &str), to: (&str, &str)) -> Result<CatalogDiff, MirrorError> {
+    let (from_name, from_dir) = from;
+    let (to_name, to_dir) = to;
+    let old = DeclarativeConfig::get_packages(&from_dir.to_string())?
+        .into_iter()
+        .collect::<BTreeSet<String>>();
+    let new = DeclarativeConfig::get_packages(&to_dir.to_string())?
+        .into_iter()
+        .collect::<BTreeSet<String>>();
+    let changed_packages = old
+        .intersection(&new)
+        .map(|pkg| {
+            diff_package(
+                pkg,
+                &get_package_config(from_dir, pkg),
+                &get_package_config(to_dir, pkg),
+            )
+        })
+        .filter(|d| !d.is_empty())
+        .collect();
+    Ok(CatalogDiff {
+        from: from_name.to_string(),
+        to: to_name.to_string(),
+        added_packages: missing(&new, &old),
+        removed_packages: missing(&old, &new),
+        changed_packages,
+    })
+}
+
+/// format the diff as text, json or markdown
+pub fn format_diff(diff: &CatalogDiff, format: &DiffFormat) -> Result<String, MirrorError> {
+    let markdown = match format {
+        DiffFormat::Json => {
+            return serde_json::to_string_pretty(diff)
+                .map_err(|e| MirrorError::new(&format!("serializing to json {}", e)))
+        }
+        DiffFormat::Markdown => true,
+        DiffFormat::Text => false,
+    };
+    let value = |v: &Option<String>| match (v, markdown) {
+        (Some(v), true) => format!("`{}`", v),
+        (Some(v), false) => v.clone(),
+        (None, _) => "(none)".to_string(),
+    };
+    let mut lines: Vec<String> = vec![];
+    let title = format!("catalog diff {} -> {}", diff.from, diff.to);
+    lines.push(match markdown {
+        true => format!("# {}", title),
+        false => title,
+    });
+    let list = |lines: &mut Vec<String>, title: &str, items: &[String], level: usize| {
+        if items.is_empty() {
+            return;
+        }
+        lines.push(match markdown {
+            true => format!("\n{} {} ({})\n", "#".repeat(level), title, items.len()),
+            false => format!("{}{} ({}):", "  ".repeat(level - 2), title, items.len()),
+        });
+        for item in items.iter() {
+            lines.push(match markdown {
+                true => format!("- {}", item),
+                false => format!("{}  {}", "  ".repeat(level - 2), item),
+            });
+        }
+    };
+    list(&mut lines, "added packages", &diff.added_packages, 2);
+    list(&mut lines, "removed packages", &diff.removed_packages, 2);
+    if !diff.changed_packages.is_empty() {
+        lines.push(match markdown {
+            true => format!("\n## changed packages ({})", diff.changed_packages.len()),
+            false => format!("changed packages ({}):", diff.changed_packages.len()),
+        });
+    }
+    for pkg in diff.changed_packages.iter() {
+        lines.push(match markdown {
+            true => format!("\n### {}", pkg.name),
+            false => format!("  {}", pkg.name),
+        });
+        if let Some(change) = pkg.default_channel.as_ref() {
+            lines.push(match markdown {
+                true => format!(
+                    "\ndefault channel: {} -> {}",
+                    value(&change.from),
+                    value(&change.to)
+                ),
+                false => format!(
+                    "    default channel: {} -> {}",
+                    value(&change.from),
+                    value(&change.to)
+                ),
+            });
+        }
+        list(&mut lines, "added channels", &pkg.added_channels, 4);
+        list(&mut lines, "removed channels", &pkg.removed_channels, 4);
+        list(&mut lines, "added bundles", &pkg.added_bundles, 4);
+        list(&mut lines, "removed bundles", &pkg.removed_bundles, 4);
+        if pkg.changed_images.is_empty() {
+            continue;
+        }
+        match markdown {
+            true => {
+                lines.push(format!(
+                    "\n#### changed images ({})\n",
+                    pkg.changed_images.len()
+                ));
+                lines.push("| bundle | name | from | to |".to_string());
+                lines.push("| --- | --- | --- | --- |".to_string());
+                for image in pkg.changed_images.iter() {
+                    lines.push(format!(
+                        "| {} | {} | {} | {} |",
+                        image.bundle,
+                        image.name,
+                        value(&image.from),
+                        value(&image.to)
+                    ));
+                }
+            }
+            false => {
+                lines.push(format!(
+                    "    changed images ({}):",
+                    pkg.changed_images.len()
+                ));
+                for image in pkg.changed_images.iter() {
+                    lines.push(format!(
+                        "      {} {}: {} -> {}",
+                        image.bundle,
+                        image.name,
+                        value(&image.from),
+                        value(&image.to)
+                    ));
+                }
+            }
+        }
+    }
+    if lines.len() == 1 {
+        lines.push(match markdown {
+            true => "\nno changes".to_string(),
+            false => "no changes".to_string(),
+        });
+    }
+    Ok(lines.join("\n"))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    fn albo_diff() -> PackageDiff {
+        diff_package(
+            "albo-operator",
+            &declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json"),
+            &declarative_config_from_file(
+                "test-artifacts/declarative-config/albo-operator-next.json",
+            ),
+        )
+    }
+
+    #[test]
+    fn diff_package_pass() {
+        let res = albo_diff();
+        assert_eq!(
+            res.default_channel,
+            Some(Change {
+                from: Some("stable-v1".to_string()),
+                to: Some("stable-v2".to_string()),
+            })
+        );
+        assert_eq!(res.added_channels, vec!["stable-v2".to_string()]);
+        assert_eq!(res.removed_channels, vec!["stable-v0".to_string()]);
+        assert_eq!(res.added_bundles, vec!["albo-operator.v2.0.0".to_string()]);
+        assert_eq!(
+            res.removed_bundles,
+            vec!["albo-operator.v0.9.0".to_string()]
+        );
+        assert_eq!(res.changed_images.len(), 1);
+        assert_eq!(res.changed_images[0].bundle, "albo-operator.v1.2.0");
+        assert_eq!(res.changed_images[0].name, "controller");
+
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        assert!(diff_package("albo-operator", &dc_map, &dc_map).is_empty());
+    }
+
+    #[test]
+    fn format_diff_pass() {
+        let diff = CatalogDiff {
+            from: "redhat-operator-index:v4.17".to_string(),
+            to: "redhat-operator-index:v4.18".to_string(),
+            added_packages: vec!["new-operator".to_string()],
+            removed_packages: vec![],
+            changed_packages: vec![albo_diff()],
+        };
+        let text = format_diff(&diff, &DiffFormat::Text).unwrap();
+        assert!(text.contains("added packages (1):\n  new-operator"));
+        assert!(!text.contains("removed packages"));
+        assert!(text.contains("    default channel: stable-v1 -> stable-v2"));
+        assert!(text.contains("      albo-operator.v1.2.0 controller: "));
+
+        let markdown = format_diff(&diff, &DiffFormat::Markdown).unwrap();
+        assert!(markdown.starts_with("# catalog diff"));
+        assert!(markdown.contains("### albo-operator"));
+        assert!(markdown.contains("default channel: `stable-v1` -> `stable-v2`"));
+        assert!(markdown.contains("| albo-operator.v1.2.0 | controller |"));
+
+        let json = format_diff(&diff, &DiffFormat::Json).unwrap();
+        assert!(json.contains("\"addedPackages\""));
+
+        let empty = CatalogDiff {
+            from: "a".to_string(),
+            to: "b".to_string(),
+            added_packages: vec![],
+            removed_packages: vec![],
+            changed_packages: vec![],
+        };
+        assert!(format_diff(&empty, &DiffFormat::Text)
+            .unwrap()
+            .ends_with("no changes"));
+    }
+}
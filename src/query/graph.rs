@@ -0,0 +1,218 @@
+use crate::operator::catalog::*;
+use crate::operator::graph::*;
+use clap::ValueEnum;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use std::collections::HashMap;
+
+/// graph export format used by 'query graph'
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    /// file extension used when exporting to a file
+    pub fn extension(&self) -> &str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Mermaid => "mmd",
+        }
+    }
+}
+
+/// get the upgrade graphs of a package (a single channel when set)
+pub fn get_package_graphs(
+    package: &str,
+    channel: Option<&str>,
+    dc_map: &HashMap<String, DeclarativeConfig>,
+) -> Result<Vec<ChannelGraph>, MirrorError> {
+    let channels = get_channels(dc_map);
+    let default_channel = get_default_channel(dc_map).unwrap_or_default();
+    if let Some(c) = channel {
+        if !channels.contains_key(c) {
+            let names = channels
+                .keys()
+                .map(|c| format!("  {}", c))
+                .collect::<Vec<String>>();
+            return Err(MirrorError::new(&format!(
+                "channel \"{}\" not found in package \"{}\", valid channels are :\n{}",
+                c,
+                package,
+                names.join("\n")
+            )));
+        }
+    }
+    Ok(channels
+        .iter()
+        .filter(|(name, _)| channel.map(|c| c == name.as_str()).unwrap_or(true))
+        .map(|(name, entries)| get_channel_graph(dc_map, name, entries, name == &default_channel))
+        .collect())
+}
+
+/// format the graphs as graphviz dot or mermaid
+pub fn format_graph(package: &str, graphs: &[ChannelGraph], format: &GraphFormat) -> String {
+    match format {
+        GraphFormat::Dot => format_dot(package, graphs),
+        GraphFormat::Mermaid => format_mermaid(graphs),
+    }
+}
+
+/// channel label (the default channel is marked)
+fn channel_label(graph: &ChannelGraph) -> String {
+    match graph.default_channel {
+        true => format!("{} (default)", graph.name),
+        false => graph.name.clone(),
+    }
+}
+
+/// format the graphs as graphviz dot (one cluster per channel)
+// node ids are prefixed with the channel as a bundle can be in more than one channel
+pub fn format_dot(package: &str, graphs: &[ChannelGraph]) -> String {
+    let mut lines = vec![
+        format!("digraph \"{}\" {{", package),
+        "  rankdir=LR;".to_string(),
+        "  node [shape=box];".to_string(),
+    ];
+    for graph in graphs.iter() {
+        let id = |name: &str| format!("\"{}/{}\"", graph.name, name);
+        lines.push(format!("  subgraph \"cluster_{}\" {{", graph.name));
+        lines.push(format!("    label=\"{}\";", channel_label(graph)));
+        if graph.default_channel {
+            lines.push("    style=bold;".to_string());
+        }
+        for node in graph.nodes.iter() {
+            let mut label = node.name.clone();
+            if let Some(range) = node.skip_range.as_ref() {
+                label = format!("{}\\nskipRange: {}", label, range);
+            }
+            let style = match node.head {
+                true => ", style=filled, fillcolor=lightblue, peripheries=2",
+                false => "",
+            };
+            if node.head {
+                label = format!("{}\\n(head)", label);
+            }
+            lines.push(format!(
+                "    {} [label=\"{}\"{}];",
+                id(&node.name),
+                label,
+                style
+            ));
+        }
+        for edge in graph.edges.iter() {
+            let style = match edge.via {
+                EdgeKind::Replaces => "",
+                EdgeKind::Skips => ", style=dashed",
+                EdgeKind::SkipRange => ", style=dotted",
+            };
+            lines.push(format!(
+                "    {} -> {} [label=\"{}\"{}];",
+                id(&edge.from),
+                id(&edge.to),
+                edge.via,
+                style
+            ));
+        }
+        lines.push("  }".to_string());
+    }
+    lines.push("}".to_string());
+    lines.join("\n")
+}
+
+/// format the graphs as a mermaid flowchart (one subgraph per channel)
+// mermaid ids can't contain dots so nodes are numbered, labels escape the range operators
+pub fn format_mermaid(graphs: &[ChannelGraph]) -> String {
+    let escape = |value: &str| value.replace('<', "#60;").replace('>', "#62;");
+    let mut lines = vec!["flowchart LR".to_string()];
+    let mut heads: Vec<String> = vec![];
+    for (c, graph) in graphs.iter().enumerate() {
+        let id = |name: &str| {
+            let pos = graph
+                .nodes
+                .iter()
+                .position(|n| n.name == name)
+                .unwrap_or_default();
+            format!("c{}n{}", c, pos)
+        };
+        lines.push(format!(
+            "  subgraph c{}[\"{}\"]",
+            c,
+            escape(&channel_label(graph))
+        ));
+        for node in graph.nodes.iter() {
+            let mut label = node.name.clone();
+            if let Some(range) = node.skip_range.as_ref() {
+                label = format!("{}<br/>skipRange: {}", label, escape(range));
+            }
+            if node.head {
+                label = format!("{}<br/>(head)", label);
+                heads.push(id(&node.name));
+            }
+            lines.push(format!("    {}[\"{}\"]", id(&node.name), label));
+        }
+        for edge in graph.edges.iter() {
+            let arrow = match edge.via {
+                EdgeKind::Replaces => "-->",
+                _ => "-.->",
+            };
+            lines.push(format!(
+                "    {} {}|{}| {}",
+                id(&edge.from),
+                arrow,
+                edge.via,
+                id(&edge.to)
+            ));
+        }
+        lines.push("  end".to_string());
+    }
+    if !heads.is_empty() {
+        lines.push("  classDef head fill:#add8e6,stroke:#333,stroke-width:2px".to_string());
+        lines.push(format!("  class {} head", heads.join(",")));
+    }
+    if let Some(c) = graphs.iter().position(|g| g.default_channel) {
+        lines.push(format!("  style c{} stroke-width:3px", c));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn format_graph_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let graphs = get_package_graphs("albo-operator", None, &dc_map).unwrap();
+        assert_eq!(graphs.len(), 2);
+
+        let dot = format_graph("albo-operator", &graphs, &GraphFormat::Dot);
+        assert!(dot.starts_with("digraph \"albo-operator\" {"));
+        assert!(dot.contains("label=\"stable-v1 (default)\";"));
+        assert!(dot.contains(
+            "\"stable-v1/albo-operator.v1.0.0\" -> \"stable-v1/albo-operator.v1.1.0\" [label=\"skips\", style=dashed];"
+        ));
+        assert!(dot.contains("peripheries=2"));
+        assert!(dot.ends_with("}"));
+
+        let mermaid = format_graph("albo-operator", &graphs, &GraphFormat::Mermaid);
+        assert!(mermaid.starts_with("flowchart LR"));
+        assert!(mermaid.contains("subgraph c1[\"stable-v1 (default)\"]"));
+        assert!(mermaid.contains("c1n0 -->|replaces| c1n1"));
+        assert!(mermaid.contains("skipRange: #62;=1.0.0 #60;1.2.0"));
+        assert!(mermaid.contains("class c0n1,c1n3 head"));
+    }
+
+    #[test]
+    fn get_package_graphs_fail() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let res = get_package_graphs("albo-operator", Some("fast"), &dc_map);
+        assert!(res.err().unwrap().to_string().contains("  stable-v1"));
+        let res = get_package_graphs("albo-operator", Some("stable-v0"), &dc_map).unwrap();
+        assert_eq!(res.len(), 1);
+    }
+}
@@ -0,0 +1,137 @@
+use crate::operator::catalog::*;
+use crate::operator::filter::PackageFilter;
+use clap::ValueEnum;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+
+/// kind of mirror config to generate
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum ConfigKind {
+    /// ImageSetConfiguration (oc-mirror)
+    Imageset,
+    /// CatalogDownloadConfiguration (used by 'update')
+    CatalogDownload,
+}
+
+/// mirror config file (ImageSetConfiguration or CatalogDownloadConfiguration)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct MirrorConfigFile {
+    pub kind: String,
+    pub api_version: String,
+    pub mirror: MirrorOperators,
+}
+
+/// operators section of the mirror config
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MirrorOperators {
+    pub operators: Vec<MirrorOperator>,
+}
+
+/// catalog entry (packages is omitted to mirror the full catalog)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct MirrorOperator {
+    pub catalog: String,
+    #[serde(skip_serializing_if = "Vec::is_empty")]
+    pub packages: Vec<PackageFilter>,
+}
+
+/// build the mirror config yaml from the selected packages of each catalog image
+pub fn format_mirror_config(
+    kind: &ConfigKind,
+    selections: &[(String, Vec<PackageFilter>)],
+) -> Result<String, MirrorError> {
+    let (kind, api_version) = match kind {
+        ConfigKind::Imageset => ("ImageSetConfiguration", "mirror.openshift.io/v2alpha1"),
+        ConfigKind::CatalogDownload => {
+            ("CatalogDownloadConfiguration", "mirror.openshift/v3alpha1")
+        }
+    };
+    let config = MirrorConfigFile {
+        kind: kind.to_string(),
+        api_version: api_version.to_string(),
+        mirror: MirrorOperators {
+            operators: selections
+                .iter()
+                .map(|(catalog, packages)| MirrorOperator {
+                    catalog: catalog.clone(),
+                    packages: packages.clone(),
+                })
+                .collect(),
+        },
+    };
+    serde_yaml::to_string(&config)
+        .map_err(|e| MirrorError::new(&format!("serializing to yaml {}", e)))
+}
+
+/// check that the selected packages and channels exist in the catalog
+pub fn check_selection(configs_dir: &str, selection: &[PackageFilter]) -> Result<(), MirrorError> {
+    let packages = DeclarativeConfig::get_packages(&configs_dir.to_string())?;
+    let mut errors: Vec<String> = vec![];
+    for filter in selection.iter() {
+        if !packages.contains(&filter.name) {
+            errors.push(format!("  package {} not found", filter.name));
+            continue;
+        }
+        let channels = get_channels(&get_package_config(configs_dir, &filter.name));
+        for channel in filter.channels.iter() {
+            if !channels.contains_key(&channel.name) {
+                errors.push(format!(
+                    "  channel {} not found in package {} (valid channels are {})",
+                    channel.name,
+                    filter.name,
+                    channels.keys().cloned().collect::<Vec<String>>().join(", ")
+                ));
+            }
+        }
+    }
+    match errors.is_empty() {
+        true => Ok(()),
+        false => Err(MirrorError::new(&format!(
+            "invalid selection :\n{}",
+            errors.join("\n")
+        ))),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::selection::parse_package_spec;
+
+    #[test]
+    fn format_mirror_config_pass() {
+        let selections = vec![
+            (
+                "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+                vec![
+                    parse_package_spec("odf-operator:stable-4.18@4.18.2..").unwrap(),
+                    parse_package_spec("3scale-operator").unwrap(),
+                ],
+            ),
+            (
+                "registry.redhat.io/redhat/certified-operator-index:v4.18".to_string(),
+                vec![],
+            ),
+        ];
+        let res = format_mirror_config(&ConfigKind::Imageset, &selections).unwrap();
+        let expected = "kind: ImageSetConfiguration
+apiVersion: mirror.openshift.io/v2alpha1
+mirror:
+  operators:
+  - catalog: registry.redhat.io/redhat/redhat-operator-index:v4.18
+    packages:
+    - name: odf-operator
+      channels:
+      - name: stable-4.18
+        minVersion: 4.18.2
+    - name: 3scale-operator
+  - catalog: registry.redhat.io/redhat/certified-operator-index:v4.18
+";
+        assert_eq!(res, expected);
+        let res = format_mirror_config(&ConfigKind::CatalogDownload, &selections).unwrap();
+        assert!(res.starts_with("kind: CatalogDownloadConfiguration\n"));
+    }
+}
@@ -0,0 +1,12 @@
+pub mod bundle;
+pub mod dependencies;
+pub mod dependents;
+pub mod diff;
+pub mod graph;
+pub mod imageset;
+pub mod output;
+pub mod packages;
+pub mod provides;
+pub mod snapshots;
+pub mod upgrade;
+pub mod validate;
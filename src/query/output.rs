@@ -0,0 +1,82 @@
+use clap::ValueEnum;
+use mirror_error::MirrorError;
+use serde::Serialize;
+
+/// output format used by the query subcommands
+#[derive(ValueEnum, Clone, Debug, PartialEq)]
+pub enum OutputFormat {
+    #[value(alias = "text")]
+    Table,
+    Json,
+    Yaml,
+}
+
+/// serialize the value as json or yaml
+pub fn to_output<T: Serialize>(value: &T, format: &OutputFormat) -> Result<String, MirrorError> {
+    match format {
+        OutputFormat::Yaml => serde_yaml::to_string(value)
+            .map_err(|e| MirrorError::new(&format!("serializing to yaml {}", e))),
+        _ => serde_json::to_string_pretty(value)
+            .map_err(|e| MirrorError::new(&format!("serializing to json {}", e))),
+    }
+}
+
+/// format the rows as a table with left aligned padded columns
+pub fn format_table(header: &[&str], rows: &[Vec<String>]) -> String {
+    let mut widths = header.iter().map(|h| h.len()).collect::<Vec<usize>>();
+    for row in rows.iter() {
+        for (i, cell) in row.iter().enumerate() {
+            if i < widths.len() && cell.len() > widths[i] {
+                widths[i] = cell.len();
+            }
+        }
+    }
+    let format_row = |cells: Vec<String>| -> String {
+        cells
+            .iter()
+            .enumerate()
+            .map(|(i, c)| format!("{:width$}", c, width = widths[i]))
+            .collect::<Vec<String>>()
+            .join("  ")
+            .trim_end()
+            .to_string()
+    };
+    let mut lines = vec![format_row(
+        header.iter().map(|h| h.to_uppercase()).collect(),
+    )];
+    for row in rows.iter() {
+        lines.push(format_row(row.clone()));
+    }
+    lines.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn format_table_pass() {
+        let res = format_table(
+            &["name", "default channel"],
+            &[
+                vec!["albo-operator".to_string(), "stable-v1".to_string()],
+                vec!["odf".to_string(), "stable-4.18".to_string()],
+            ],
+        );
+        assert_eq!(
+            res,
+            "NAME           DEFAULT CHANNEL\nalbo-operator  stable-v1\nodf            stable-4.18"
+        );
+    }
+
+    #[test]
+    fn to_output_pass() {
+        let value = vec!["a".to_string()];
+        assert_eq!(
+            to_output(&value, &OutputFormat::Json).unwrap(),
+            "[\n  \"a\"\n]"
+        );
+        assert_eq!(to_output(&value, &OutputFormat::Yaml).unwrap(), "- a\n");
+    }
+}
@@ -0,0 +1,127 @@
+use crate::operator::bundle::get_bundle_version;
+use crate::operator::catalog::*;
+use crate::operator::filter::{filter_packages, PackageFilter};
+use crate::query::output::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::HashMap;
+
+/// summary of a package used by 'query packages'
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct PackageSummary {
+    pub name: String,
+    pub default_channel: Option<String>,
+    pub channel_count: usize,
+    pub latest_bundle: Option<String>,
+    pub latest_version: Option<String>,
+}
+
+/// summarize a package from its declarative config map
+// the latest bundle is the head of the default channel
+pub fn get_package_summary(
+    name: &str,
+    dc_map: &HashMap<String, DeclarativeConfig>,
+) -> PackageSummary {
+    let default_channel = get_default_channel(dc_map);
+    let channels = get_channels(dc_map);
+    let latest_bundle = default_channel
+        .as_ref()
+        .and_then(|c| channels.get(c))
+        .and_then(|entries| get_channel_head(dc_map, entries));
+    let latest_version = latest_bundle
+        .as_ref()
+        .and_then(|b| get_bundle_version(dc_map, b))
+        .map(|v| v.to_string());
+    PackageSummary {
+        name: name.to_string(),
+        default_channel,
+        channel_count: channels.len(),
+        latest_bundle,
+        latest_version,
+    }
+}
+
+/// summarize all packages of a catalog (restricted to the filters if set)
+pub fn query_packages(
+    configs_dir: &str,
+    filters: &[PackageFilter],
+) -> Result<Vec<PackageSummary>, MirrorError> {
+    let packages = DeclarativeConfig::get_packages(&configs_dir.to_string())?;
+    let mut summaries = filter_packages(&packages, filters)
+        .iter()
+        .map(|pkg| get_package_summary(pkg, &get_package_config(configs_dir, pkg)))
+        .collect::<Vec<PackageSummary>>();
+    summaries.sort_by(|a, b| a.name.cmp(&b.name));
+    Ok(summaries)
+}
+
+/// format the package summaries
+pub fn format_packages(
+    summaries: &[PackageSummary],
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(&summaries, format);
+    }
+    let rows = summaries
+        .iter()
+        .map(|s| {
+            vec![
+                s.name.clone(),
+                s.default_channel.clone().unwrap_or_default(),
+                s.channel_count.to_string(),
+                s.latest_bundle.clone().unwrap_or_default(),
+                s.latest_version.clone().unwrap_or_default(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    Ok(format_table(
+        &[
+            "name",
+            "default channel",
+            "channels",
+            "latest bundle",
+            "version",
+        ],
+        &rows,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn get_package_summary_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let res = get_package_summary("albo-operator", &dc_map);
+        assert_eq!(
+            res,
+            PackageSummary {
+                name: "albo-operator".to_string(),
+                default_channel: Some("stable-v1".to_string()),
+                channel_count: 2,
+                latest_bundle: Some("albo-operator.v1.2.0".to_string()),
+                latest_version: Some("1.2.0".to_string()),
+            }
+        );
+    }
+
+    #[test]
+    fn format_packages_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let summaries = vec![get_package_summary("albo-operator", &dc_map)];
+        let table = format_packages(&summaries, &OutputFormat::Table).unwrap();
+        assert!(table.starts_with("NAME "));
+        assert!(table.contains("albo-operator.v1.2.0"));
+        let json = format_packages(&summaries, &OutputFormat::Json).unwrap();
+        assert!(json.contains("\"defaultChannel\": \"stable-v1\""));
+        let yaml = format_packages(&summaries, &OutputFormat::Yaml).unwrap();
+        assert!(yaml.contains("channelCount: 2"));
+    }
+}
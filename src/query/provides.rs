@@ -0,0 +1,87 @@
+use crate::operator::bundle::Gvk;
+use crate::operator::provides::*;
+use crate::query::output::*;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+
+/// gvk with the bundles that provide it used by 'query provides'
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ProvidedGvk {
+    pub gvk: Gvk,
+    pub providers: Vec<GvkProvider>,
+}
+
+/// find the packages and bundles that provide the gvk (group/version/kind, group/kind or kind)
+pub fn query_provides(index: &GvkIndex, query: &str) -> Result<Vec<ProvidedGvk>, MirrorError> {
+    let parsed = GvkQuery::parse(query)?;
+    let res = index
+        .lookup(&parsed)
+        .into_iter()
+        .map(|(gvk, providers)| ProvidedGvk {
+            gvk: gvk.clone(),
+            providers: providers.clone(),
+        })
+        .collect::<Vec<ProvidedGvk>>();
+    if res.is_empty() {
+        return Err(MirrorError::new(&format!(
+            "no package in the catalog provides {}",
+            query
+        )));
+    }
+    Ok(res)
+}
+
+/// format the providers as table, json or yaml
+// the head of the default channel of each package is marked with *
+pub fn format_provides(
+    provided: &[ProvidedGvk],
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(&provided, format);
+    }
+    let mut rows: Vec<Vec<String>> = vec![];
+    for p in provided.iter() {
+        for provider in p.providers.iter() {
+            rows.push(vec![
+                p.gvk.to_string(),
+                provider.package.clone(),
+                format!(
+                    "{}{}",
+                    provider.bundle,
+                    if provider.default_head { " *" } else { "" }
+                ),
+                provider.version.clone().unwrap_or_default(),
+            ]);
+        }
+    }
+    Ok(format_table(
+        &["gvk", "package", "bundle", "version"],
+        &rows,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::catalog_index_from_files;
+
+    #[test]
+    fn query_provides_pass() {
+        let index = GvkIndex::new(&catalog_index_from_files(&[
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]));
+        let res = query_provides(&index, "cert-manager.io/v1/Certificate").unwrap();
+        let text = format_provides(&res, &OutputFormat::Table).unwrap();
+        let expected = "GVK                             PACKAGE                BUNDLE                           VERSION
+cert-manager.io/v1/Certificate  cert-manager-operator  cert-manager-operator.v1.12.0 *  1.12.0
+cert-manager.io/v1/Certificate  cert-manager-operator  cert-manager-operator.v1.11.0    1.11.0";
+        assert_eq!(text, expected);
+        let err = query_provides(&index, "LogForwarder").err().unwrap();
+        assert!(err
+            .to_string()
+            .contains("no package in the catalog provides"));
+    }
+}
@@ -0,0 +1,117 @@
+use crate::cli::config::ViewState;
+use crate::query::output::*;
+use chrono::{DateTime, Utc};
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+
+/// snapshot of a catalog used by 'snapshots list' (the current contents are listed first)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct SnapshotSummary {
+    pub id: String,
+    pub digest: Option<String>,
+    pub last_update: Option<DateTime<Utc>>,
+    pub archived: Option<DateTime<Utc>>,
+    pub configs_dir: String,
+}
+
+/// list the current contents and the snapshots of a catalog (name:version/arch)
+pub fn query_snapshots(
+    state: &ViewState,
+    catalog: &str,
+) -> Result<Vec<SnapshotSummary>, MirrorError> {
+    let (key, arch) = catalog.split_once('/').unwrap_or((catalog, "amd64"));
+    let arch_state = state
+        .get_arch(key, arch)
+        .ok_or(MirrorError::new(&format!("catalog {} not found", catalog)))?;
+    let mut summaries = vec![SnapshotSummary {
+        id: "current".to_string(),
+        digest: arch_state.digest.clone(),
+        last_update: arch_state.last_update,
+        archived: None,
+        configs_dir: arch_state.configs_dir.clone(),
+    }];
+    for snapshot in arch_state.snapshots.iter() {
+        summaries.push(SnapshotSummary {
+            id: snapshot.id.clone(),
+            digest: snapshot.digest.clone(),
+            last_update: snapshot.last_update,
+            archived: Some(snapshot.archived),
+            configs_dir: snapshot.configs_dir.clone(),
+        });
+    }
+    Ok(summaries)
+}
+
+/// format the snapshots
+pub fn format_snapshots(
+    summaries: &[SnapshotSummary],
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(&summaries, format);
+    }
+    let time = |t: &Option<DateTime<Utc>>| {
+        t.map(|t| t.format("%Y-%m-%d %H:%M:%S").to_string())
+            .unwrap_or_default()
+    };
+    let rows = summaries
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            vec![
+                match i {
+                    0 => String::new(),
+                    _ => i.to_string(),
+                },
+                s.id.clone(),
+                s.digest.clone().unwrap_or_default(),
+                time(&s.last_update),
+                time(&s.archived),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    Ok(format_table(
+        &["#", "id", "digest", "last update", "archived"],
+        &rows,
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::cli::config::SnapshotState;
+
+    #[test]
+    fn query_snapshots_pass() {
+        let mut state = ViewState::new();
+        state.update_catalog(
+            "redhat-operator-index:v4.18".to_string(),
+            "registry.redhat.io/redhat/redhat-operator-index:v4.18".to_string(),
+            None,
+            "amd64".to_string(),
+            Some("sha256:2222".to_string()),
+            "/tmp/cache/configs/".to_string(),
+        );
+        state.add_snapshot(
+            "redhat-operator-index:v4.18",
+            "amd64",
+            SnapshotState {
+                id: "20250101T000000Z-1111".to_string(),
+                digest: Some("sha256:1111".to_string()),
+                path: "/tmp/snapshots/20250101T000000Z-1111".to_string(),
+                configs_dir: "/tmp/snapshots/20250101T000000Z-1111/configs/".to_string(),
+                last_update: None,
+                archived: Utc::now(),
+            },
+        );
+        let res = query_snapshots(&state, "redhat-operator-index:v4.18/amd64").unwrap();
+        assert_eq!(res.len(), 2);
+        assert_eq!(res[0].id, "current");
+        assert_eq!(res[1].digest, Some("sha256:1111".to_string()));
+        let text = format_snapshots(&res, &OutputFormat::Table).unwrap();
+        assert!(text.contains("1  20250101T000000Z-1111  sha256:1111"));
+        assert!(query_snapshots(&state, "redhat-operator-index:v4.17/amd64").is_err());
+    }
+}
@@ -0,0 +1,130 @@
+use crate::operator::catalog::*;
+use crate::operator::graph::*;
+use crate::query::output::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use std::collections::HashMap;
+
+/// upgrade path between two bundles used by 'query upgrade-path'
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct UpgradePath {
+    pub package: String,
+    pub channel: String,
+    pub from: String,
+    pub to: String,
+    pub steps: Vec<UpgradeEdge>,
+}
+
+/// compute the upgrade path of a package
+// the channel defaults to the default channel and the target to the channel head
+pub fn query_upgrade_path(
+    package: &str,
+    channel: Option<&str>,
+    from: &str,
+    to: Option<&str>,
+    dc_map: &HashMap<String, DeclarativeConfig>,
+) -> Result<UpgradePath, MirrorError> {
+    let channels = get_channels(dc_map);
+    let channel = match channel {
+        Some(c) => c.to_string(),
+        None => get_default_channel(dc_map).ok_or(MirrorError::new(&format!(
+            "package \"{}\" has no default channel, please use --channel",
+            package
+        )))?,
+    };
+    let entries = match channels.get(&channel) {
+        Some(entries) => entries,
+        None => {
+            let names = channels
+                .keys()
+                .map(|c| format!("  {}", c))
+                .collect::<Vec<String>>();
+            return Err(MirrorError::new(&format!(
+                "channel \"{}\" not found in package \"{}\", valid channels are :\n{}",
+                channel,
+                package,
+                names.join("\n")
+            )));
+        }
+    };
+    let to = match to {
+        Some(to) => to.to_string(),
+        None => get_channel_head(dc_map, entries).ok_or(MirrorError::new(&format!(
+            "channel \"{}\" has no head",
+            channel
+        )))?,
+    };
+    let steps = get_upgrade_path(dc_map, &channel, entries, from, &to)?;
+    Ok(UpgradePath {
+        package: package.to_string(),
+        channel,
+        from: from.to_string(),
+        to,
+        steps,
+    })
+}
+
+/// format the upgrade path
+pub fn format_upgrade_path(
+    path: &UpgradePath,
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(path, format);
+    }
+    if path.steps.is_empty() {
+        return Ok(format!("{} is already at {}", path.from, path.to));
+    }
+    let rows = path
+        .steps
+        .iter()
+        .enumerate()
+        .map(|(i, s)| {
+            vec![
+                (i + 1).to_string(),
+                s.from.clone(),
+                s.to.clone(),
+                s.via.to_string(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    Ok(format_table(&["step", "from", "to", "via"], &rows))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn query_upgrade_path_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        // defaults to the head of the default channel
+        let res = query_upgrade_path("albo-operator", None, "albo-operator.v0.9.0", None, &dc_map)
+            .unwrap();
+        assert_eq!(res.channel, "stable-v1");
+        assert_eq!(res.to, "albo-operator.v1.2.0");
+        assert_eq!(res.steps.len(), 2);
+        let text = format_upgrade_path(&res, &OutputFormat::Table).unwrap();
+        assert!(text.starts_with("STEP"));
+        assert!(text.contains("albo-operator.v1.0.1  albo-operator.v1.2.0  skipRange"));
+        let json = format_upgrade_path(&res, &OutputFormat::Json).unwrap();
+        assert!(json.contains("\"via\": \"skipRange\""));
+    }
+
+    #[test]
+    fn query_upgrade_path_fail() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let res = query_upgrade_path(
+            "albo-operator",
+            Some("fast"),
+            "albo-operator.v0.9.0",
+            None,
+            &dc_map,
+        );
+        assert!(res.err().unwrap().to_string().contains("  stable-v0"));
+    }
+}
@@ -0,0 +1,137 @@
+use crate::operator::catalog::*;
+use crate::operator::validate::*;
+use crate::query::output::*;
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+
+/// result of 'validate' for a catalog
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct ValidationReport {
+    pub catalog: String,
+    pub packages: usize,
+    pub errors: usize,
+    pub warnings: usize,
+    pub issues: Vec<Issue>,
+}
+
+impl ValidationReport {
+    pub fn new(catalog: &str, packages: usize, mut issues: Vec<Issue>) -> Self {
+        issues.sort_by(|a, b| {
+            (&a.package, &a.severity, &a.check).cmp(&(&b.package, &b.severity, &b.check))
+        });
+        let errors = issues
+            .iter()
+            .filter(|i| i.severity == Severity::Error)
+            .count();
+        Self {
+            catalog: catalog.to_string(),
+            packages,
+            errors,
+            warnings: issues.len() - errors,
+            issues,
+        }
+    }
+}
+
+/// validate the declarative configs of every package (or the given packages) of a catalog
+pub fn query_validate(
+    catalog: &str,
+    configs_dir: &str,
+    packages: &[String],
+) -> Result<ValidationReport, MirrorError> {
+    let all = DeclarativeConfig::get_packages(&configs_dir.to_string())?;
+    let selected = match packages.is_empty() {
+        true => all.clone(),
+        false => packages.to_vec(),
+    };
+    let mut issues: Vec<Issue> = vec![];
+    for pkg in selected.iter() {
+        if !all.contains(pkg) {
+            return Err(MirrorError::new(&format!(
+                "package {} not found in catalog",
+                pkg
+            )));
+        }
+        // fall back to the merged configs when the original files can't be read
+        let configs = match load_package_fbc(configs_dir, pkg) {
+            Ok(configs) if !configs.is_empty() => configs,
+            _ => get_package_config(configs_dir, pkg).into_values().collect(),
+        };
+        issues.extend(validate_package(pkg, &configs));
+    }
+    Ok(ValidationReport::new(catalog, selected.len(), issues))
+}
+
+/// format the report as table, json or yaml
+pub fn format_validation(
+    report: &ValidationReport,
+    format: &OutputFormat,
+) -> Result<String, MirrorError> {
+    if format != &OutputFormat::Table {
+        return to_output(report, format);
+    }
+    let summary = format!(
+        "{} packages validated, {} errors, {} warnings",
+        report.packages, report.errors, report.warnings
+    );
+    if report.issues.is_empty() {
+        return Ok(summary);
+    }
+    let rows = report
+        .issues
+        .iter()
+        .map(|i| {
+            vec![
+                match i.severity {
+                    Severity::Error => "error".to_string(),
+                    Severity::Warning => "warning".to_string(),
+                },
+                i.package.clone(),
+                i.check.clone(),
+                i.message.clone(),
+            ]
+        })
+        .collect::<Vec<Vec<String>>>();
+    Ok(format!(
+        "{}\n\n{}",
+        format_table(&["severity", "package", "check", "message"], &rows),
+        summary
+    ))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn format_validation_pass() {
+        let mut issues = vec![];
+        for pkg in ["albo-operator", "broken-operator"] {
+            let configs = load_package_fbc("test-artifacts/fbc-configs/", pkg).unwrap();
+            issues.extend(validate_package(pkg, &configs));
+        }
+        let report = ValidationReport::new("test-index:v1.0", 2, issues);
+        assert_eq!(report.errors, 6);
+        assert_eq!(report.warnings, 3);
+        let text = format_validation(&report, &OutputFormat::Table).unwrap();
+        assert!(text.starts_with("SEVERITY  PACKAGE          CHECK"));
+        assert!(text.contains(
+            "warning   broken-operator  orphaned-bundle          bundle broken-operator.v0.9.0 is not in any channel"
+        ));
+        assert!(text.ends_with("2 packages validated, 6 errors, 3 warnings"));
+        let json = format_validation(&report, &OutputFormat::Json).unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["issues"][0]["severity"], "warning");
+        assert_eq!(value["issues"][0]["check"], "dangling-replaces");
+        assert_eq!(value["issues"][1]["severity"], "error");
+        assert_eq!(value["issues"][1]["check"], "bundle-without-image");
+
+        let report = ValidationReport::new("test-index:v1.0", 1, vec![]);
+        assert_eq!(
+            format_validation(&report, &OutputFormat::Table).unwrap(),
+            "1 packages validated, 0 errors, 0 warnings"
+        );
+    }
+}
@@ -0,0 +1,625 @@
+// tonic::Status is large, but it is what the generated service expects
+#![allow(clippy::result_large_err)]
+
+use crate::operator::bundle::*;
+use crate::operator::catalog::*;
+use crate::operator::dependency::CatalogIndex;
+use api::registry_server::{Registry, RegistryServer};
+use custom_logger::*;
+use mirror_catalog::{ChannelEntry, DeclarativeConfig};
+use mirror_error::MirrorError;
+use serde_json::json;
+use std::collections::HashMap;
+use std::net::SocketAddr;
+use std::sync::Arc;
+use tokio::net::TcpListener;
+use tokio_stream::wrappers::TcpListenerStream;
+use tonic::transport::Server;
+use tonic::{Request, Response, Status};
+
+/// messages and service generated from proto/registry.proto (operator-registry pkg/api)
+pub mod api {
+    tonic::include_proto!("api");
+
+    pub const FILE_DESCRIPTOR_SET: &[u8] =
+        tonic::include_file_descriptor_set!("registry_descriptor");
+}
+
+// server streaming responses are built in memory (catalogs are small enough)
+type ListStream<T> = tokio_stream::Iter<std::vec::IntoIter<Result<T, Status>>>;
+
+/// OLM registry api (as served by 'opm serve') over the declarative configs of a catalog
+#[derive(Debug, Clone, Default)]
+pub struct RegistryService {
+    pub index: Arc<CatalogIndex>,
+}
+
+/// channel entry of a package with its bundle
+struct Entry<'a> {
+    package: &'a str,
+    channel: String,
+    entry: ChannelEntry,
+    bundle: &'a DeclarativeConfig,
+}
+
+impl RegistryService {
+    pub fn new(index: CatalogIndex) -> Self {
+        Self {
+            index: Arc::new(index),
+        }
+    }
+
+    fn get_dc_map(&self, pkg: &str) -> Result<&HashMap<String, DeclarativeConfig>, Status> {
+        self.index
+            .packages
+            .get(pkg)
+            .ok_or(Status::not_found(format!("package {} not found", pkg)))
+    }
+
+    /// every channel entry of the catalog (used by the rpcs that search across packages)
+    fn entries(&self) -> Vec<Entry<'_>> {
+        self.index
+            .packages
+            .iter()
+            .flat_map(|(pkg, dc_map)| package_entries(pkg, dc_map, None))
+            .collect()
+    }
+
+    /// channel entries of a single channel of a package
+    fn channel_entries(&self, pkg: &str, channel: &str) -> Result<Vec<Entry<'_>>, Status> {
+        let (pkg, dc_map) = self
+            .index
+            .packages
+            .get_key_value(pkg)
+            .ok_or(Status::not_found(format!("package {} not found", pkg)))?;
+        Ok(package_entries(pkg, dc_map, Some(channel)))
+    }
+
+    /// entries of the channel heads
+    fn heads(&self) -> Vec<Entry<'_>> {
+        let mut heads: HashMap<(&str, String), String> = HashMap::new();
+        for (pkg, dc_map) in self.index.packages.iter() {
+            for (channel, entries) in get_channels(dc_map).into_iter() {
+                if let Some(head) = get_channel_head(dc_map, &entries) {
+                    heads.insert((pkg.as_str(), channel), head);
+                }
+            }
+        }
+        self.entries()
+            .into_iter()
+            .filter(|e| heads.get(&(e.package, e.channel.clone())) == Some(&e.entry.name))
+            .collect()
+    }
+
+    /// get a channel entry of a package
+    fn get_entry(&self, pkg: &str, channel: &str, name: &str) -> Result<Entry<'_>, Status> {
+        self.channel_entries(pkg, channel)?
+            .into_iter()
+            .find(|e| e.entry.name == name)
+            .ok_or(Status::not_found(format!(
+                "bundle {} not found in channel {} of package {}",
+                name, channel, pkg
+            )))
+    }
+}
+
+/// channel entries of a package, or of one of its channels (entries without a bundle are skipped)
+fn package_entries<'a>(
+    pkg: &'a str,
+    dc_map: &'a HashMap<String, DeclarativeConfig>,
+    channel: Option<&str>,
+) -> Vec<Entry<'a>> {
+    let mut entries: Vec<Entry> = vec![];
+    for (name, channel_entries) in get_channels(dc_map).into_iter() {
+        if channel.is_some_and(|c| c != name) {
+            continue;
+        }
+        for entry in channel_entries.into_iter() {
+            if let Some(bundle) = dc_map.get(&format!("{}=olm.bundle", entry.name)) {
+                entries.push(Entry {
+                    package: pkg,
+                    channel: name.clone(),
+                    entry,
+                    bundle,
+                });
+            }
+        }
+    }
+    entries
+}
+
+/// the entry replaces (or skips) the bundle
+fn replaces(entry: &ChannelEntry, name: &str) -> bool {
+    entry.replaces.as_deref() == Some(name)
+        || entry
+            .skips
+            .as_ref()
+            .map(|s| s.iter().any(|s| s == name))
+            .unwrap_or(false)
+}
+
+fn to_gvk(gvk: Gvk) -> api::GroupVersionKind {
+    api::GroupVersionKind {
+        group: gvk.group,
+        version: gvk.version,
+        kind: gvk.kind,
+        plural: String::new(),
+    }
+}
+
+fn from_request(group: &str, version: &str, kind: &str) -> Gvk {
+    Gvk {
+        group: group.to_string(),
+        version: version.to_string(),
+        kind: kind.to_string(),
+    }
+}
+
+fn to_channel_entry(entry: &Entry, replaces: Option<String>) -> api::ChannelEntry {
+    api::ChannelEntry {
+        package_name: entry.package.to_string(),
+        channel_name: entry.channel.clone(),
+        bundle_name: entry.entry.name.clone(),
+        replaces: replaces.unwrap_or_default(),
+    }
+}
+
+/// convert a bundle to the api message (as opm does for fbc catalogs)
+// csvJson and object are left empty, the bundle objects are not kept in the declarative configs
+fn to_bundle(entry: &Entry) -> api::Bundle {
+    let dc = entry.bundle;
+    let mut dependencies = get_required_packages(dc)
+        .into_iter()
+        .map(|r| api::Dependency {
+            r#type: "olm.package".to_string(),
+            value: json!({
+                "packageName": r.package_name,
+                "version": r.version_range.unwrap_or_default(),
+            })
+            .to_string(),
+        })
+        .collect::<Vec<api::Dependency>>();
+    dependencies.extend(
+        get_gvks(dc, "olm.gvk.required")
+            .into_iter()
+            .map(|gvk| api::Dependency {
+                r#type: "olm.gvk".to_string(),
+                value: serde_json::to_string(&gvk).unwrap_or_default(),
+            }),
+    );
+    let properties = dc
+        .properties
+        .iter()
+        .flatten()
+        .map(|p| {
+            // drop the fields that are not set for this property type
+            let mut value = serde_json::to_value(&p.value).unwrap_or_default();
+            if let Some(map) = value.as_object_mut() {
+                map.retain(|_, v| !v.is_null());
+            }
+            api::Property {
+                r#type: p.type_prop.clone(),
+                value: value.to_string(),
+            }
+        })
+        .collect();
+    let version = get_properties(dc, "olm.package")
+        .iter()
+        .find_map(|v| v.get("version").and_then(|v| v.as_str()).map(String::from))
+        .unwrap_or_default();
+    api::Bundle {
+        csv_name: entry.entry.name.clone(),
+        package_name: entry.package.to_string(),
+        channel_name: entry.channel.clone(),
+        csv_json: String::new(),
+        object: vec![],
+        bundle_path: dc.image.clone().unwrap_or_default(),
+        provided_apis: get_gvks(dc, "olm.gvk").into_iter().map(to_gvk).collect(),
+        required_apis: get_gvks(dc, "olm.gvk.required")
+            .into_iter()
+            .map(to_gvk)
+            .collect(),
+        version,
+        skip_range: entry.entry.skip_range.clone().unwrap_or_default(),
+        dependencies,
+        properties,
+        replaces: entry.entry.replaces.clone().unwrap_or_default(),
+        skips: entry.entry.skips.clone().unwrap_or_default(),
+        deprecation: None,
+    }
+}
+
+#[tonic::async_trait]
+impl Registry for RegistryService {
+    type ListPackagesStream = ListStream<api::PackageName>;
+    type GetChannelEntriesThatReplaceStream = ListStream<api::ChannelEntry>;
+    type GetChannelEntriesThatProvideStream = ListStream<api::ChannelEntry>;
+    type GetLatestChannelEntriesThatProvideStream = ListStream<api::ChannelEntry>;
+    type ListBundlesStream = ListStream<api::Bundle>;
+
+    async fn list_packages(
+        &self,
+        _request: Request<api::ListPackageRequest>,
+    ) -> Result<Response<Self::ListPackagesStream>, Status> {
+        let names = self
+            .index
+            .packages
+            .keys()
+            .map(|name| Ok(api::PackageName { name: name.clone() }))
+            .collect::<Vec<_>>();
+        Ok(Response::new(tokio_stream::iter(names)))
+    }
+
+    async fn get_package(
+        &self,
+        request: Request<api::GetPackageRequest>,
+    ) -> Result<Response<api::Package>, Status> {
+        let name = request.into_inner().name;
+        let dc_map = self.get_dc_map(&name)?;
+        let channels = get_channels(dc_map)
+            .iter()
+            .map(|(channel, entries)| api::Channel {
+                name: channel.clone(),
+                csv_name: get_channel_head(dc_map, entries).unwrap_or_default(),
+                deprecation: None,
+            })
+            .collect();
+        Ok(Response::new(api::Package {
+            name,
+            channels,
+            default_channel_name: get_default_channel(dc_map).unwrap_or_default(),
+            deprecation: None,
+        }))
+    }
+
+    async fn get_bundle(
+        &self,
+        request: Request<api::GetBundleRequest>,
+    ) -> Result<Response<api::Bundle>, Status> {
+        let req = request.into_inner();
+        let entry = self.get_entry(&req.pkg_name, &req.channel_name, &req.csv_name)?;
+        Ok(Response::new(to_bundle(&entry)))
+    }
+
+    async fn get_bundle_for_channel(
+        &self,
+        request: Request<api::GetBundleInChannelRequest>,
+    ) -> Result<Response<api::Bundle>, Status> {
+        let req = request.into_inner();
+        let dc_map = self.get_dc_map(&req.pkg_name)?;
+        let head = get_channels(dc_map)
+            .get(&req.channel_name)
+            .and_then(|entries| get_channel_head(dc_map, entries))
+            .ok_or(Status::not_found(format!(
+                "channel {} not found in package {}",
+                req.channel_name, req.pkg_name
+            )))?;
+        let entry = self.get_entry(&req.pkg_name, &req.channel_name, &head)?;
+        Ok(Response::new(to_bundle(&entry)))
+    }
+
+    async fn get_channel_entries_that_replace(
+        &self,
+        request: Request<api::GetAllReplacementsRequest>,
+    ) -> Result<Response<Self::GetChannelEntriesThatReplaceStream>, Status> {
+        let name = request.into_inner().csv_name;
+        let entries = self
+            .entries()
+            .iter()
+            .filter(|e| replaces(&e.entry, &name))
+            .map(|e| Ok(to_channel_entry(e, Some(name.clone()))))
+            .collect::<Vec<_>>();
+        Ok(Response::new(tokio_stream::iter(entries)))
+    }
+
+    async fn get_bundle_that_replaces(
+        &self,
+        request: Request<api::GetReplacementRequest>,
+    ) -> Result<Response<api::Bundle>, Status> {
+        let req = request.into_inner();
+        let entry = self
+            .channel_entries(&req.pkg_name, &req.channel_name)?
+            .into_iter()
+            .find(|e| replaces(&e.entry, &req.csv_name))
+            .ok_or(Status::not_found(format!(
+                "no bundle replaces {} in channel {} of package {}",
+                req.csv_name, req.channel_name, req.pkg_name
+            )))?;
+        Ok(Response::new(to_bundle(&entry)))
+    }
+
+    async fn get_channel_entries_that_provide(
+        &self,
+        request: Request<api::GetAllProvidersRequest>,
+    ) -> Result<Response<Self::GetChannelEntriesThatProvideStream>, Status> {
+        let req = request.into_inner();
+        let gvk = from_request(&req.group, &req.version, &req.kind);
+        let mut entries = vec![];
+        for e in self.entries().iter() {
+            if !get_gvks(e.bundle, "olm.gvk").contains(&gvk) {
+                continue;
+            }
+            // one entry per upgrade edge (replaces and skips)
+            entries.push(Ok(to_channel_entry(e, e.entry.replaces.clone())));
+            for skip in e.entry.skips.iter().flatten() {
+                entries.push(Ok(to_channel_entry(e, Some(skip.clone()))));
+            }
+        }
+        Ok(Response::new(tokio_stream::iter(entries)))
+    }
+
+    async fn get_latest_channel_entries_that_provide(
+        &self,
+        request: Request<api::GetLatestProvidersRequest>,
+    ) -> Result<Response<Self::GetLatestChannelEntriesThatProvideStream>, Status> {
+        let req = request.into_inner();
+        let gvk = from_request(&req.group, &req.version, &req.kind);
+        let entries = self
+            .heads()
+            .iter()
+            .filter(|e| get_gvks(e.bundle, "olm.gvk").contains(&gvk))
+            .map(|e| Ok(to_channel_entry(e, e.entry.replaces.clone())))
+            .collect::<Vec<_>>();
+        Ok(Response::new(tokio_stream::iter(entries)))
+    }
+
+    async fn get_default_bundle_that_provides(
+        &self,
+        request: Request<api::GetDefaultProviderRequest>,
+    ) -> Result<Response<api::Bundle>, Status> {
+        let req = request.into_inner();
+        let gvk = from_request(&req.group, &req.version, &req.kind);
+        let entry = self
+            .heads()
+            .into_iter()
+            .filter(|e| {
+                let dc_map = &self.index.packages[e.package];
+                get_default_channel(dc_map).as_ref() == Some(&e.channel)
+            })
+            .find(|e| get_gvks(e.bundle, "olm.gvk").contains(&gvk))
+            .ok_or(Status::not_found(format!(
+                "no default channel head provides {}",
+                gvk
+            )))?;
+        Ok(Response::new(to_bundle(&entry)))
+    }
+
+    async fn list_bundles(
+        &self,
+        _request: Request<api::ListBundlesRequest>,
+    ) -> Result<Response<Self::ListBundlesStream>, Status> {
+        let bundles = self
+            .entries()
+            .iter()
+            .map(|e| Ok(to_bundle(e)))
+            .collect::<Vec<_>>();
+        Ok(Response::new(tokio_stream::iter(bundles)))
+    }
+
+    async fn get_deprecations_for_package(
+        &self,
+        request: Request<api::GetDeprecationsForPackageRequest>,
+    ) -> Result<Response<api::Deprecations>, Status> {
+        // olm.deprecations objects are not part of the declarative configs we keep
+        self.get_dc_map(&request.into_inner().pkg_name)?;
+        Ok(Response::new(api::Deprecations {
+            deprecations: vec![],
+        }))
+    }
+}
+
+/// bind the registry api to the address, returns the bound address and the server future
+// the grpc health and reflection services are served as well (used by OLM and grpcurl)
+pub async fn bind(
+    address: &SocketAddr,
+    service: RegistryService,
+) -> Result<
+    (
+        SocketAddr,
+        impl std::future::Future<Output = Result<(), tonic::transport::Error>>,
+    ),
+    MirrorError,
+> {
+    let listener = TcpListener::bind(address)
+        .await
+        .map_err(|e| MirrorError::new(&format!("binding to {} {}", address, e)))?;
+    let address = listener
+        .local_addr()
+        .map_err(|e| MirrorError::new(&format!("reading bound address {}", e)))?;
+    let (mut reporter, health) = tonic_health::server::health_reporter();
+    reporter
+        .set_serving::<RegistryServer<RegistryService>>()
+        .await;
+    let reflection = tonic_reflection::server::Builder::configure()
+        .register_encoded_file_descriptor_set(api::FILE_DESCRIPTOR_SET)
+        .build()
+        .map_err(|e| MirrorError::new(&format!("building reflection service {}", e)))?;
+    info!("[bind] serving {} packages", service.index.packages.len());
+    let server = Server::builder()
+        .add_service(health)
+        .add_service(reflection)
+        .add_service(RegistryServer::new(service))
+        .serve_with_incoming(TcpListenerStream::new(listener));
+    Ok((address, server))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use api::registry_client::RegistryClient;
+    use tokio_stream::StreamExt;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn registry_service() -> RegistryService {
+        RegistryService::new(catalog_index_from_files(&[
+            "albo-operator",
+            "cert-manager-operator",
+            "monitoring-operator",
+        ]))
+    }
+
+    #[test]
+    fn registry_pass() {
+        aw!(async {
+            let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+            let (address, server) = bind(&address, registry_service()).await.unwrap();
+            tokio::spawn(server);
+            let mut client = RegistryClient::connect(format!("http://{}", address))
+                .await
+                .unwrap();
+
+            let names = client
+                .list_packages(api::ListPackageRequest {})
+                .await
+                .unwrap()
+                .into_inner()
+                .map(|p| p.unwrap().name)
+                .collect::<Vec<String>>()
+                .await;
+            assert_eq!(
+                names,
+                vec![
+                    "albo-operator",
+                    "cert-manager-operator",
+                    "monitoring-operator"
+                ]
+            );
+
+            let pkg = client
+                .get_package(api::GetPackageRequest {
+                    name: "albo-operator".to_string(),
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(pkg.default_channel_name, "stable-v1");
+            assert_eq!(pkg.channels[0].name, "stable-v0");
+            assert_eq!(pkg.channels[0].csv_name, "albo-operator.v1.0.0");
+            assert_eq!(pkg.channels[1].csv_name, "albo-operator.v1.2.0");
+
+            let bundle = client
+                .get_bundle(api::GetBundleRequest {
+                    pkg_name: "albo-operator".to_string(),
+                    channel_name: "stable-v1".to_string(),
+                    csv_name: "albo-operator.v1.1.0".to_string(),
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(
+                bundle.bundle_path,
+                "registry.example.com/albo/albo-operator-bundle@sha256:1100"
+            );
+            assert_eq!(bundle.version, "1.1.0");
+            assert_eq!(bundle.replaces, "albo-operator.v1.0.1");
+            assert_eq!(bundle.skips, vec!["albo-operator.v1.0.0"]);
+            assert_eq!(bundle.provided_apis[0].kind, "TargetGroupBinding");
+            assert_eq!(bundle.dependencies[0].r#type, "olm.package");
+            let value: serde_json::Value =
+                serde_json::from_str(&bundle.dependencies[0].value).unwrap();
+            assert_eq!(value["packageName"], "cert-manager-operator");
+            assert_eq!(value["version"], ">=1.10.0");
+
+            let bundle = client
+                .get_bundle_for_channel(api::GetBundleInChannelRequest {
+                    pkg_name: "cert-manager-operator".to_string(),
+                    channel_name: "stable-v1".to_string(),
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(bundle.csv_name, "cert-manager-operator.v1.12.0");
+
+            let entries = client
+                .get_channel_entries_that_replace(api::GetAllReplacementsRequest {
+                    csv_name: "albo-operator.v1.0.0".to_string(),
+                })
+                .await
+                .unwrap()
+                .into_inner()
+                .map(|e| {
+                    let e = e.unwrap();
+                    (e.channel_name, e.bundle_name)
+                })
+                .collect::<Vec<_>>()
+                .await;
+            assert_eq!(
+                entries,
+                vec![
+                    ("stable-v1".to_string(), "albo-operator.v1.0.1".to_string()),
+                    ("stable-v1".to_string(), "albo-operator.v1.1.0".to_string()),
+                ]
+            );
+
+            let bundle = client
+                .get_default_bundle_that_provides(api::GetDefaultProviderRequest {
+                    group: "monitoring.example.com".to_string(),
+                    version: "v1".to_string(),
+                    kind: "ServiceMonitor".to_string(),
+                    plural: String::new(),
+                })
+                .await
+                .unwrap()
+                .into_inner();
+            assert_eq!(bundle.csv_name, "cert-manager-operator.v1.12.0");
+
+            let bundles = client
+                .list_bundles(api::ListBundlesRequest {})
+                .await
+                .unwrap()
+                .into_inner()
+                .collect::<Vec<_>>()
+                .await;
+            // albo-operator.v1.0.0 is in both channels
+            assert_eq!(bundles.len(), 10);
+        });
+    }
+
+    #[test]
+    fn registry_fail() {
+        aw!(async {
+            let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+            let (address, server) = bind(&address, registry_service()).await.unwrap();
+            tokio::spawn(server);
+            let mut client = RegistryClient::connect(format!("http://{}", address))
+                .await
+                .unwrap();
+            let err = client
+                .get_package(api::GetPackageRequest {
+                    name: "none".to_string(),
+                })
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(err.code(), tonic::Code::NotFound);
+            assert_eq!(err.message(), "package none not found");
+            let err = client
+                .get_bundle(api::GetBundleRequest {
+                    pkg_name: "albo-operator".to_string(),
+                    channel_name: "stable-v0".to_string(),
+                    csv_name: "albo-operator.v1.2.0".to_string(),
+                })
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(err.code(), tonic::Code::NotFound);
+            let err = client
+                .get_default_bundle_that_provides(api::GetDefaultProviderRequest {
+                    group: "logging.example.com".to_string(),
+                    version: "v1".to_string(),
+                    kind: "LogForwarder".to_string(),
+                    plural: String::new(),
+                })
+                .await
+                .err()
+                .unwrap();
+            assert_eq!(err.code(), tonic::Code::NotFound);
+        });
+    }
+}
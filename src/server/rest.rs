@@ -0,0 +1,494 @@
+use crate::cli::config::ViewState;
+use crate::operator::bundle::get_bundle_version;
+use crate::operator::catalog::*;
+use crate::operator::dependency::*;
+use crate::query::bundle::*;
+use crate::query::packages::*;
+use crate::query::upgrade::*;
+use custom_logger::*;
+use hyper::service::{make_service_fn, service_fn};
+use hyper::{Body, Method, Request, Response, Server, StatusCode};
+use mirror_catalog::DeclarativeConfig;
+use mirror_error::MirrorError;
+use serde_derive::Serialize;
+use serde_json::json;
+use std::collections::{BTreeMap, HashMap};
+use std::convert::Infallible;
+use std::net::SocketAddr;
+use std::sync::{Arc, Mutex};
+
+/// registered catalog served by the api (key is name:version/arch)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct CatalogSummary {
+    pub name: String,
+    pub arch: String,
+    pub image: String,
+    pub configs_dir: String,
+}
+
+/// channel of a package with its entries
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct ChannelSummary {
+    pub name: String,
+    pub default: bool,
+    pub head: Option<String>,
+    pub entries: Vec<EntrySummary>,
+}
+
+/// channel entry (bundle with its upgrade edges)
+#[derive(Serialize, Debug, Clone, PartialEq)]
+#[serde(rename_all = "camelCase")]
+pub struct EntrySummary {
+    pub name: String,
+    pub replaces: Option<String>,
+    pub skips: Vec<String>,
+    pub skip_range: Option<String>,
+}
+
+/// bundle of a package with its version
+#[derive(Serialize, Debug, Clone, PartialEq)]
+pub struct BundleSummary {
+    pub name: String,
+    pub version: Option<String>,
+    pub image: Option<String>,
+}
+
+/// catalogs served by the api, the packages of a catalog are loaded on first use
+#[derive(Debug, Default)]
+pub struct ServerState {
+    pub catalogs: BTreeMap<String, CatalogSummary>,
+    indexes: Mutex<HashMap<String, Arc<CatalogIndex>>>,
+}
+
+/// api error (status code and message returned as {"error": message})
+#[derive(Debug, Clone, PartialEq)]
+pub struct ApiError {
+    pub status: StatusCode,
+    pub message: String,
+}
+
+impl ApiError {
+    fn not_found(message: String) -> Self {
+        Self {
+            status: StatusCode::NOT_FOUND,
+            message,
+        }
+    }
+
+    fn bad_request(message: String) -> Self {
+        Self {
+            status: StatusCode::BAD_REQUEST,
+            message,
+        }
+    }
+}
+
+impl From<MirrorError> for ApiError {
+    fn from(err: MirrorError) -> Self {
+        Self::not_found(err.to_string())
+    }
+}
+
+impl ServerState {
+    /// register every catalog (and architecture) of the state file
+    pub fn new(state: &ViewState) -> Self {
+        let images = state.images_map();
+        let mut catalogs = BTreeMap::new();
+        for (key, configs_dir) in state.configs_map().iter() {
+            let (name, arch) = key.split_once('/').unwrap_or((key, "amd64"));
+            catalogs.insert(
+                key.clone(),
+                CatalogSummary {
+                    name: name.to_string(),
+                    arch: arch.to_string(),
+                    image: images.get(key).cloned().unwrap_or_default(),
+                    configs_dir: configs_dir.clone(),
+                },
+            );
+        }
+        Self {
+            catalogs,
+            indexes: Mutex::new(HashMap::new()),
+        }
+    }
+
+    /// register a catalog with its packages already loaded
+    #[cfg(test)]
+    pub fn add_catalog(&mut self, catalog: CatalogSummary, index: CatalogIndex) {
+        let key = format!("{}/{}", catalog.name, catalog.arch);
+        self.indexes
+            .lock()
+            .unwrap()
+            .insert(key.clone(), Arc::new(index));
+        self.catalogs.insert(key, catalog);
+    }
+
+    /// get the packages of a catalog (loads them on first use)
+    pub fn get_index(&self, name: &str, arch: &str) -> Result<Arc<CatalogIndex>, ApiError> {
+        let key = format!("{}/{}", name, arch);
+        let catalog = self
+            .catalogs
+            .get(&key)
+            .ok_or(ApiError::not_found(format!("catalog {} not found", key)))?;
+        if let Some(index) = self.indexes.lock().unwrap().get(&key) {
+            return Ok(index.clone());
+        }
+        info!("[get_index] loading catalog {}", key);
+        let index = Arc::new(CatalogIndex::load(&catalog.configs_dir)?);
+        self.indexes.lock().unwrap().insert(key, index.clone());
+        Ok(index)
+    }
+
+    /// get the configs directory of a catalog
+    pub fn get_configs_dir(&self, name: &str, arch: &str) -> Result<String, ApiError> {
+        let key = format!("{}/{}", name, arch);
+        self.catalogs
+            .get(&key)
+            .map(|c| c.configs_dir.clone())
+            .ok_or(ApiError::not_found(format!("catalog {} not found", key)))
+    }
+
+    /// get the declarative config map of a package of a catalog
+    pub fn get_package(
+        &self,
+        name: &str,
+        arch: &str,
+        pkg: &str,
+    ) -> Result<HashMap<String, DeclarativeConfig>, ApiError> {
+        self.get_index(name, arch)?
+            .packages
+            .get(pkg)
+            .cloned()
+            .ok_or(ApiError::not_found(format!("package {} not found", pkg)))
+    }
+}
+
+/// handle a GET request, returns the json body
+// routes (catalog is name:version i.e redhat-operator-index:v4.18, ?arch= defaults to amd64) :
+//   /api/v1/catalogs
+//   /api/v1/catalogs/{catalog}/packages
+//   /api/v1/catalogs/{catalog}/packages/{package}
+//   /api/v1/catalogs/{catalog}/packages/{package}/channels
+//   /api/v1/catalogs/{catalog}/packages/{package}/bundles
+//   /api/v1/catalogs/{catalog}/packages/{package}/bundles/{bundle}
+//   /api/v1/catalogs/{catalog}/packages/{package}/bundles/{bundle}/related-images
+//   /api/v1/catalogs/{catalog}/packages/{package}/bundles/{bundle}/dependencies
+//   /api/v1/catalogs/{catalog}/packages/{package}/upgrade-path?from=&to=&channel=
+pub fn route(
+    state: &ServerState,
+    path: &str,
+    query: &HashMap<String, String>,
+) -> Result<serde_json::Value, ApiError> {
+    let segments = path
+        .trim_matches('/')
+        .split('/')
+        .map(|s| urlencoding::decode(s).map(|s| s.to_string()))
+        .collect::<Result<Vec<String>, _>>()
+        .map_err(|e| ApiError::bad_request(format!("invalid path {}", e)))?;
+    let segments = segments.iter().map(|s| s.as_str()).collect::<Vec<&str>>();
+    let arch = query.get("arch").map(|a| a.as_str()).unwrap_or("amd64");
+    let to_json = |value: serde_json::Result<serde_json::Value>| {
+        value.map_err(|e| ApiError {
+            status: StatusCode::INTERNAL_SERVER_ERROR,
+            message: format!("serializing to json {}", e),
+        })
+    };
+    match segments.as_slice() {
+        ["api", "v1", "catalogs"] => to_json(serde_json::to_value(
+            state.catalogs.values().collect::<Vec<_>>(),
+        )),
+        ["api", "v1", "catalogs", catalog, "packages"] => {
+            let index = state.get_index(catalog, arch)?;
+            let summaries = index
+                .packages
+                .iter()
+                .map(|(name, dc_map)| get_package_summary(name, dc_map))
+                .collect::<Vec<PackageSummary>>();
+            to_json(serde_json::to_value(summaries))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            to_json(serde_json::to_value(get_package_summary(pkg, &dc_map)))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "channels"] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            let default_channel = get_default_channel(&dc_map);
+            let channels = get_channels(&dc_map)
+                .into_iter()
+                .map(|(name, entries)| ChannelSummary {
+                    default: default_channel.as_ref() == Some(&name),
+                    head: get_channel_head(&dc_map, &entries),
+                    entries: entries
+                        .iter()
+                        .map(|e| EntrySummary {
+                            name: e.name.clone(),
+                            replaces: e.replaces.clone(),
+                            skips: e.skips.clone().unwrap_or_default(),
+                            skip_range: e.skip_range.clone(),
+                        })
+                        .collect(),
+                    name,
+                })
+                .collect::<Vec<ChannelSummary>>();
+            to_json(serde_json::to_value(channels))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "bundles"] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            let mut bundles = get_bundles(&dc_map)
+                .iter()
+                .map(|(name, dc)| BundleSummary {
+                    name: name.clone(),
+                    version: get_bundle_version(&dc_map, name).map(|v| v.to_string()),
+                    image: dc.image.clone(),
+                })
+                .collect::<Vec<BundleSummary>>();
+            bundles.sort_by_key(|b| get_bundle_version(&dc_map, &b.name));
+            to_json(serde_json::to_value(bundles))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "bundles", bundle] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            let configs_dir = state.get_configs_dir(catalog, arch)?;
+            to_json(serde_json::to_value(get_bundle_details(
+                pkg,
+                bundle,
+                &dc_map,
+                &configs_dir,
+            )?))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "bundles", bundle, "related-images"] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            let configs_dir = state.get_configs_dir(catalog, arch)?;
+            let details = get_bundle_details(pkg, bundle, &dc_map, &configs_dir)?;
+            to_json(serde_json::to_value(details.related_images))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "bundles", bundle, "dependencies"] => {
+            let index = state.get_index(catalog, arch)?;
+            to_json(serde_json::to_value(resolve_dependencies(
+                &index, pkg, bundle,
+            )?))
+        }
+        ["api", "v1", "catalogs", catalog, "packages", pkg, "upgrade-path"] => {
+            let dc_map = state.get_package(catalog, arch, pkg)?;
+            let from = query.get("from").ok_or(ApiError::bad_request(
+                "the from query parameter is required".to_string(),
+            ))?;
+            let path = query_upgrade_path(
+                pkg,
+                query.get("channel").map(|c| c.as_str()),
+                from,
+                query.get("to").map(|t| t.as_str()),
+                &dc_map,
+            )
+            .map_err(|e| ApiError::bad_request(e.to_string()))?;
+            to_json(serde_json::to_value(path))
+        }
+        _ => Err(ApiError::not_found(format!("route {} not found", path))),
+    }
+}
+
+/// parse the query string of a request (values are url decoded)
+pub fn parse_query(query: Option<&str>) -> HashMap<String, String> {
+    query
+        .unwrap_or_default()
+        .split('&')
+        .filter_map(|kv| kv.split_once('=').or(Some((kv, ""))))
+        .filter(|(k, _)| !k.is_empty())
+        .map(|(k, v)| {
+            let v = v.replace('+', " ");
+            (
+                k.to_string(),
+                urlencoding::decode(&v)
+                    .map(|v| v.to_string())
+                    .unwrap_or(v.clone()),
+            )
+        })
+        .collect()
+}
+
+async fn handle(state: Arc<ServerState>, req: Request<Body>) -> Result<Response<Body>, Infallible> {
+    let path = req.uri().path().to_string();
+    let query = parse_query(req.uri().query());
+    let res = match req.method() {
+        &Method::GET => {
+            // catalogs are loaded from disk on first use
+            let state = state.clone();
+            tokio::task::spawn_blocking(move || route(&state, &path, &query))
+                .await
+                .unwrap_or_else(|e| {
+                    Err(ApiError {
+                        status: StatusCode::INTERNAL_SERVER_ERROR,
+                        message: e.to_string(),
+                    })
+                })
+        }
+        _ => Err(ApiError {
+            status: StatusCode::METHOD_NOT_ALLOWED,
+            message: format!("method {} not allowed", req.method()),
+        }),
+    };
+    let (status, body) = match res {
+        Ok(value) => (StatusCode::OK, value),
+        Err(err) => (err.status, json!({ "error": err.message })),
+    };
+    debug!("[handle] {} {} {}", req.method(), req.uri(), status);
+    Ok(Response::builder()
+        .status(status)
+        .header("content-type", "application/json")
+        .body(Body::from(body.to_string()))
+        .unwrap())
+}
+
+/// bind the api to the address, returns the bound address and the server future
+// use port 0 to bind to a free port
+pub fn bind(
+    address: &SocketAddr,
+    state: Arc<ServerState>,
+) -> Result<
+    (
+        SocketAddr,
+        impl std::future::Future<Output = hyper::Result<()>>,
+    ),
+    MirrorError,
+> {
+    let make_svc = make_service_fn(move |_| {
+        let state = state.clone();
+        async move { Ok::<_, Infallible>(service_fn(move |req| handle(state.clone(), req))) }
+    });
+    let server = Server::try_bind(address)
+        .map_err(|e| MirrorError::new(&format!("binding to {} {}", address, e)))?
+        .serve(make_svc);
+    Ok((server.local_addr(), server))
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    macro_rules! aw {
+        ($e:expr) => {
+            tokio_test::block_on($e)
+        };
+    }
+
+    fn server_state() -> ServerState {
+        let mut state = ServerState::default();
+        state.add_catalog(
+            CatalogSummary {
+                name: "test-index:v1.0".to_string(),
+                arch: "amd64".to_string(),
+                image: "registry.example.com/test/test-index:v1.0".to_string(),
+                configs_dir: "/tmp/test-index/configs/".to_string(),
+            },
+            catalog_index_from_files(&[
+                "albo-operator",
+                "cert-manager-operator",
+                "monitoring-operator",
+            ]),
+        );
+        state
+    }
+
+    #[test]
+    fn route_pass() {
+        let state = server_state();
+        let query = HashMap::new();
+        let res = route(&state, "/api/v1/catalogs/test-index:v1.0/packages", &query).unwrap();
+        assert_eq!(res.as_array().unwrap().len(), 3);
+        assert_eq!(res[0]["latestBundle"], "albo-operator.v1.2.0");
+
+        let res = route(
+            &state,
+            "/api/v1/catalogs/test-index%3Av1.0/packages/albo-operator/channels",
+            &query,
+        )
+        .unwrap();
+        assert_eq!(res[1]["name"], "stable-v1");
+        assert_eq!(res[1]["default"], true);
+        assert_eq!(res[1]["entries"][1]["skipRange"], ">=0.9.0 <1.0.1");
+
+        let res = route(
+            &state,
+            "/api/v1/catalogs/test-index:v1.0/packages/albo-operator/bundles/albo-operator.v1.2.0/related-images",
+            &query,
+        )
+        .unwrap();
+        assert_eq!(res[1]["name"], "controller");
+
+        let query = parse_query(Some("from=albo-operator.v1.0.0&channel=stable-v1"));
+        let res = route(
+            &state,
+            "/api/v1/catalogs/test-index:v1.0/packages/albo-operator/upgrade-path",
+            &query,
+        )
+        .unwrap();
+        assert_eq!(res["to"], "albo-operator.v1.2.0");
+        assert_eq!(res["steps"].as_array().unwrap().len(), 1);
+    }
+
+    #[test]
+    fn route_fail() {
+        let state = server_state();
+        let query = HashMap::new();
+        let err = route(&state, "/api/v1/catalogs/other:v1.0/packages", &query)
+            .err()
+            .unwrap();
+        assert_eq!(err.status, StatusCode::NOT_FOUND);
+        let err = route(
+            &state,
+            "/api/v1/catalogs/test-index:v1.0/packages/albo-operator/upgrade-path",
+            &query,
+        )
+        .err()
+        .unwrap();
+        assert_eq!(err.status, StatusCode::BAD_REQUEST);
+        let query = parse_query(Some("arch=arm64"));
+        assert!(route(&state, "/api/v1/catalogs/test-index:v1.0/packages", &query).is_err());
+    }
+
+    #[test]
+    fn serve_pass() {
+        aw!(async {
+            let address: SocketAddr = "127.0.0.1:0".parse().unwrap();
+            let (address, server) = bind(&address, Arc::new(server_state())).unwrap();
+            tokio::spawn(server);
+            let url = format!("http://{}/api/v1/catalogs", address);
+            let res = reqwest::get(&url).await.unwrap();
+            assert_eq!(res.status(), 200);
+            assert_eq!(
+                res.headers()["content-type"].to_str().unwrap(),
+                "application/json"
+            );
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body[0]["name"], "test-index:v1.0");
+
+            let url = format!(
+                "http://{}/api/v1/catalogs/test-index:v1.0/packages/albo-operator/bundles/albo-operator.v1.2.0/dependencies",
+                address
+            );
+            let body: serde_json::Value = reqwest::get(&url).await.unwrap().json().await.unwrap();
+            assert_eq!(
+                body["dependencies"][0]["bundle"],
+                "cert-manager-operator.v1.12.0"
+            );
+
+            let url = format!(
+                "http://{}/api/v1/catalogs/test-index:v1.0/packages/none",
+                address
+            );
+            let res = reqwest::get(&url).await.unwrap();
+            assert_eq!(res.status(), 404);
+            let body: serde_json::Value = res.json().await.unwrap();
+            assert_eq!(body["error"], "package none not found");
+
+            let res = reqwest::Client::new()
+                .post(format!("http://{}/api/v1/catalogs", address))
+                .send()
+                .await
+                .unwrap();
+            assert_eq!(res.status(), 405);
+        });
+    }
+}
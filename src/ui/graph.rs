@@ -0,0 +1,295 @@
+use crate::operator::graph::*;
+use ratatui::prelude::*;
+
+/// type of a line in the upgrade graph pane
+#[derive(Debug, Clone, PartialEq)]
+pub enum GraphLineKind {
+    Node { head: bool },
+    SkipRange,
+    Connector,
+}
+
+/// line of the upgrade graph pane (node lines carry the bundle name)
+#[derive(Debug, Clone, PartialEq)]
+pub struct GraphLine {
+    pub text: String,
+    pub node: Option<String>,
+    pub kind: GraphLineKind,
+}
+
+// edge between the rows of two nodes (top is the newer node), lane 0 is the node column
+#[derive(Debug, Clone, PartialEq)]
+struct EdgeSpan {
+    top: usize,
+    bottom: usize,
+    solid: bool,
+    lane: usize,
+}
+
+/// draw the channel graph as lines (head on top, one row per node)
+// edges between consecutive rows are drawn in the node column, longer edges get their own
+// lane on the right (git log --graph style), replaces are drawn solid and skips dashed,
+// skipRange is summarized as an annotation (it can match a large number of bundles)
+pub fn graph_lines(graph: &ChannelGraph) -> Vec<GraphLine> {
+    let nodes = graph.nodes.iter().rev().collect::<Vec<&GraphNode>>();
+    let spans = edge_spans(graph, &nodes);
+    let width = spans.iter().map(|s| s.lane).max().unwrap_or(0) * 2 + 1;
+    // lanes of the edges that pass between row and row + 1
+    let between = |row: usize| {
+        let mut chars = vec![' '; width];
+        for span in spans.iter().filter(|s| s.top <= row && row < s.bottom) {
+            chars[span.lane * 2] = if span.solid { '│' } else { '┆' };
+        }
+        chars
+    };
+    let mut lines: Vec<GraphLine> = vec![];
+    for (row, node) in nodes.iter().enumerate() {
+        let mut chars = vec![' '; width];
+        chars[0] = if node.head { '◉' } else { '●' };
+        let touching = spans
+            .iter()
+            .filter(|s| s.lane > 0 && (s.top == row || s.bottom == row))
+            .collect::<Vec<&EdgeSpan>>();
+        let reach = touching.iter().map(|s| s.lane * 2).max().unwrap_or(0);
+        for (pos, c) in chars.iter_mut().enumerate().take(reach + 1).skip(1) {
+            let solid = touching.iter().any(|s| s.solid && s.lane * 2 >= pos);
+            *c = if solid { '─' } else { '┄' };
+        }
+        for span in spans.iter().filter(|s| s.lane > 0) {
+            let pos = span.lane * 2;
+            if span.top == row {
+                chars[pos] = '╮';
+            } else if span.bottom == row {
+                chars[pos] = '╯';
+            } else if span.top < row && row < span.bottom {
+                chars[pos] = match (pos < reach, span.solid) {
+                    (true, _) => '┼',
+                    (false, true) => '│',
+                    (false, false) => '┆',
+                };
+            }
+        }
+        let suffix = if node.head { " (head)" } else { "" };
+        lines.push(GraphLine {
+            text: format!(
+                "{} {}{}",
+                chars.iter().collect::<String>(),
+                node.name,
+                suffix
+            ),
+            node: Some(node.name.clone()),
+            kind: GraphLineKind::Node { head: node.head },
+        });
+        if let Some(range) = node.skip_range.as_ref() {
+            let count = graph
+                .edges
+                .iter()
+                .filter(|e| e.to == node.name && e.via == EdgeKind::SkipRange)
+                .count();
+            lines.push(GraphLine {
+                text: format!(
+                    "{} skipRange {} ({} in channel)",
+                    between(row).iter().collect::<String>(),
+                    range,
+                    count
+                ),
+                node: None,
+                kind: GraphLineKind::SkipRange,
+            });
+        }
+        if row + 1 < nodes.len() {
+            lines.push(GraphLine {
+                text: between(row)
+                    .iter()
+                    .collect::<String>()
+                    .trim_end()
+                    .to_string(),
+                node: None,
+                kind: GraphLineKind::Connector,
+            });
+        }
+    }
+    lines
+}
+
+// get the replaces and skips edges as spans between rows, lanes are assigned so that
+// the spans of a lane don't touch (skipRange edges are not drawn)
+fn edge_spans(graph: &ChannelGraph, nodes: &[&GraphNode]) -> Vec<EdgeSpan> {
+    let row = |name: &str| nodes.iter().position(|n| n.name == name);
+    let mut spans: Vec<EdgeSpan> = vec![];
+    for edge in graph.edges.iter().filter(|e| e.via != EdgeKind::SkipRange) {
+        let (from, to) = match (row(&edge.from), row(&edge.to)) {
+            (Some(from), Some(to)) if from != to => (from, to),
+            _ => continue,
+        };
+        let (top, bottom) = (from.min(to), from.max(to));
+        let solid = edge.via == EdgeKind::Replaces;
+        match spans
+            .iter_mut()
+            .find(|s| s.top == top && s.bottom == bottom)
+        {
+            Some(span) => span.solid |= solid,
+            None => spans.push(EdgeSpan {
+                top,
+                bottom,
+                solid,
+                lane: 0,
+            }),
+        }
+    }
+    spans.sort_by_key(|s| (s.top, s.bottom));
+    // last row used by each lane (lane 0 is kept for consecutive rows)
+    let mut lane_ends: Vec<usize> = vec![];
+    for span in spans.iter_mut().filter(|s| s.bottom > s.top + 1) {
+        span.lane = match lane_ends.iter().position(|end| *end < span.top) {
+            Some(lane) => {
+                lane_ends[lane] = span.bottom;
+                lane + 1
+            }
+            None => {
+                lane_ends.push(span.bottom);
+                lane_ends.len()
+            }
+        };
+    }
+    spans
+}
+
+/// style of a graph line
+pub fn graph_line_style(kind: &GraphLineKind) -> Style {
+    match kind {
+        GraphLineKind::Node { head: true } => Style::default()
+            .fg(Color::LightBlue)
+            .add_modifier(Modifier::BOLD),
+        GraphLineKind::Node { head: false } => Style::default().fg(Color::LightYellow),
+        GraphLineKind::Connector => Style::default().fg(Color::White),
+        GraphLineKind::SkipRange => Style::default()
+            .fg(Color::Gray)
+            .add_modifier(Modifier::ITALIC),
+    }
+}
+
+/// get the position of the next node line (wraps around)
+pub fn next_node(lines: &[GraphLine], current: usize, forward: bool) -> Option<usize> {
+    let len = lines.len();
+    (1..=len)
+        .map(|step| match forward {
+            true => (current + step) % len,
+            false => (current + len * step - step) % len,
+        })
+        .find(|pos| lines[*pos].node.is_some())
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+    use crate::operator::catalog::*;
+
+    #[test]
+    fn graph_lines_pass() {
+        let dc_map =
+            declarative_config_from_file("test-artifacts/declarative-config/albo-operator.json");
+        let channels = get_channels(&dc_map);
+        let graph = get_channel_graph(
+            &dc_map,
+            "stable-v1",
+            channels.get("stable-v1").unwrap(),
+            true,
+        );
+        let lines = graph_lines(&graph);
+        let text = lines
+            .iter()
+            .map(|l| l.text.clone())
+            .collect::<Vec<String>>();
+        // v1.1.0 skips v1.0.0 (two rows down) so the edge is drawn in its own lane
+        assert_eq!(
+            text,
+            vec![
+                "◉   albo-operator.v1.2.0 (head)",
+                "│   skipRange >=1.0.0 <1.2.0 (2 in channel)",
+                "│",
+                "●┄╮ albo-operator.v1.1.0",
+                "│ ┆",
+                "● ┆ albo-operator.v1.0.1",
+                "│ ┆ skipRange >=0.9.0 <1.0.1 (0 in channel)",
+                "│ ┆",
+                "●┄╯ albo-operator.v1.0.0",
+            ]
+        );
+        assert_eq!(
+            lines.last().unwrap().node.as_deref(),
+            Some("albo-operator.v1.0.0")
+        );
+        assert_eq!(lines.iter().filter(|l| l.node.is_some()).count(), 4);
+    }
+
+    #[test]
+    fn graph_lines_branches_pass() {
+        let node = |name: &str, head: bool| GraphNode {
+            name: name.to_string(),
+            version: None,
+            skip_range: None,
+            head,
+        };
+        let edge = |from: &str, to: &str, via: EdgeKind| UpgradeEdge {
+            from: from.to_string(),
+            to: to.to_string(),
+            via,
+        };
+        // v1.1 and v1.2 both replace v1.0, v2.0 replaces v1.2 and skips v1.1
+        let graph = ChannelGraph {
+            name: "stable".to_string(),
+            default_channel: true,
+            nodes: vec![
+                node("v1.0", false),
+                node("v1.1", false),
+                node("v1.2", false),
+                node("v2.0", true),
+            ],
+            edges: vec![
+                edge("v1.0", "v1.1", EdgeKind::Replaces),
+                edge("v1.0", "v1.2", EdgeKind::Replaces),
+                edge("v1.2", "v2.0", EdgeKind::Replaces),
+                edge("v1.1", "v2.0", EdgeKind::Skips),
+                edge("v1.0", "v2.0", EdgeKind::SkipRange),
+            ],
+        };
+        let text = graph_lines(&graph)
+            .iter()
+            .map(|l| l.text.clone())
+            .collect::<Vec<String>>();
+        assert_eq!(
+            text,
+            vec![
+                "◉┄╮   v2.0 (head)",
+                "│ ┆",
+                "●─┼─╮ v1.2",
+                "  ┆ │",
+                "●┄╯ │ v1.1",
+                "│   │",
+                "●───╯ v1.0",
+            ]
+        );
+    }
+
+    #[test]
+    fn next_node_pass() {
+        let node = |name: &str| GraphLine {
+            text: name.to_string(),
+            node: Some(name.to_string()),
+            kind: GraphLineKind::Node { head: false },
+        };
+        let edge = GraphLine {
+            text: "│".to_string(),
+            node: None,
+            kind: GraphLineKind::Connector,
+        };
+        let lines = vec![node("a"), edge.clone(), edge, node("b")];
+        assert_eq!(next_node(&lines, 0, true), Some(3));
+        assert_eq!(next_node(&lines, 3, true), Some(0));
+        assert_eq!(next_node(&lines, 0, false), Some(3));
+        assert_eq!(next_node(&lines, 3, false), Some(0));
+        assert_eq!(next_node(&[], 0, true), None);
+    }
+}
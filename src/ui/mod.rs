@@ -1 +1,3 @@
+pub mod graph;
 pub mod render;
+pub mod search;
@@ -1,10 +1,27 @@
+use crate::operator::bundle::{get_bundle_version, parse_version};
+use crate::operator::catalog::{get_channel_head, get_default_channel, get_package_config};
+use crate::operator::dependency::*;
+use crate::operator::dependents::ReverseIndex;
+use crate::operator::filter::*;
+use crate::operator::graph::{get_channel_graph, get_upgrade_path};
+use crate::operator::provides::GvkIndex;
+use crate::operator::selection::*;
+use crate::query::dependencies::dependency_lines;
+use crate::query::dependents::format_dependents;
+use crate::query::graph::*;
+use crate::query::imageset::*;
+use crate::query::output::OutputFormat;
+use crate::ui::graph::*;
+use crate::ui::search::*;
 use crossterm::event::{self, Event, KeyCode, KeyEventKind};
 use mirror_catalog::*;
+use mirror_error::MirrorError;
 use ratatui::layout::Flex;
 use ratatui::widgets::ListState;
 use ratatui::{prelude::*, widgets::*};
 use std::collections::HashMap;
-use std::{env, io};
+use std::path::Path;
+use std::{env, fs, io};
 
 #[derive(Debug, Clone)]
 pub struct StatefulList<T> {
@@ -63,10 +80,45 @@ pub struct App {
     pub path: String,
     pub last_update: usize,
     pub show_popup: bool,
+    pub catalogs: StatefulList<String>,
+    pub catalog_map: HashMap<String, String>,
+    pub show_catalogs: bool,
+    pub show_help: bool,
+    pub all_packages: Vec<String>,
+    pub search: String,
+    pub search_mode: bool,
+    pub filters: Vec<PackageFilter>,
+    pub filter_map: HashMap<String, Vec<PackageFilter>>,
+    pub show_all: bool,
+    pub show_upgrade: bool,
+    pub message: String,
+    pub show_graph: bool,
+    pub graph: StatefulList<GraphLine>,
+    pub graph_channel: String,
+    // packages marked for mirroring (keyed by catalog)
+    pub selections: HashMap<String, Vec<PackageFilter>>,
+    pub catalog_images: HashMap<String, String>,
+    // directory of the graphs exported with 'e' (a sub directory per catalog)
+    pub export_dir: String,
+    // all packages of the current catalog (loaded on first use by the dependency popup)
+    pub catalog_index: Option<(String, CatalogIndex)>,
+    // title and lines of the dependency (p) or dependents (r) popup
+    pub dependencies: Option<(String, Vec<String>)>,
+    pub gvk_index: GvkIndex,
+    pub reverse_index: ReverseIndex,
+    pub provides_mode: bool,
+    pub provides_query: String,
+    // gvk headers (no package) followed by their providing bundles
+    pub provides: StatefulList<(String, Option<String>)>,
 }
 
 impl App {
-    pub fn new(name: String, base_dir: String) -> Self {
+    pub fn new(
+        name: String,
+        base_dir: String,
+        catalog_map: HashMap<String, String>,
+        filter_map: HashMap<String, Vec<PackageFilter>>,
+    ) -> Self {
         let this_base_dir = base_dir.clone().to_owned();
         let hld_packages = DeclarativeConfig::get_packages(&this_base_dir.clone().to_string());
         let mut packages: Vec<String> = vec![];
@@ -75,26 +127,591 @@ impl App {
         } else {
             packages = hld_packages.unwrap();
         }
-        // actually should find the first item in the list
-        // rather than hard code it
-        let dc_map = DeclarativeConfig::get_declarativeconfig_map(
-            this_base_dir.clone().to_string() + &"3scale-operator/updated-configs/",
-        );
+        // start with the first package of the selected catalog
+        let dc_map = match packages.first() {
+            Some(pkg) => get_package_config(&this_base_dir, pkg),
+            None => HashMap::new(),
+        };
 
         let title = format!("catalog viewer [ {} ]", name);
 
-        Self {
+        let mut catalog_names = catalog_map.keys().cloned().collect::<Vec<String>>();
+        catalog_names.sort();
+        let mut catalogs = StatefulList::with_items(catalog_names.clone());
+        if let Some(pos) = catalog_names.iter().position(|c| c == &name) {
+            catalogs.state.select(Some(pos));
+        }
+
+        let filters = filter_map.get(&name).cloned().unwrap_or_default();
+
+        let mut app = Self {
             name: title.clone(),
-            packages: StatefulList::with_items(packages),
+            packages: StatefulList::with_items(packages.clone()),
             channels: StatefulList::with_items(vec![]),
             declarative_config: dc_map,
             path: this_base_dir.clone(),
             last_update: 999,
             show_popup: false,
+            catalogs,
+            catalog_map,
+            show_catalogs: false,
+            show_help: false,
+            all_packages: packages,
+            search: String::new(),
+            search_mode: false,
+            filters,
+            filter_map,
+            show_all: false,
+            show_upgrade: false,
+            message: String::new(),
+            show_graph: false,
+            graph: StatefulList::with_items(vec![]),
+            graph_channel: String::new(),
+            selections: HashMap::new(),
+            catalog_images: HashMap::new(),
+            export_dir: String::new(),
+            catalog_index: None,
+            dependencies: None,
+            gvk_index: GvkIndex::default(),
+            reverse_index: ReverseIndex::default(),
+            provides_mode: false,
+            provides_query: String::new(),
+            provides: StatefulList::with_items(vec![]),
+        };
+        app.apply_filter();
+        app
+    }
+
+    /// switch to the catalog selected in the catalog picker
+    // keeps the currently selected package if it exists in the new catalog
+    pub fn switch_catalog(&mut self) {
+        let selected = match self.catalogs.state.selected() {
+            Some(id) if id < self.catalogs.items.len() => self.catalogs.items[id].clone(),
+            _ => return,
+        };
+        let base_dir = match self.catalog_map.get(&selected) {
+            Some(dir) => dir.clone(),
+            None => return,
+        };
+        let packages = match DeclarativeConfig::get_packages(&base_dir) {
+            Ok(packages) if !packages.is_empty() => packages,
+            _ => {
+                self.name = format!(
+                    "catalog viewer [ {} ] (unable to load {})",
+                    self.current_catalog(),
+                    selected
+                );
+                return;
+            }
+        };
+        self.all_packages = packages;
+        self.filters = self.filter_map.get(&selected).cloned().unwrap_or_default();
+        self.apply_filter();
+        self.channels = StatefulList::with_items(vec![]);
+        self.declarative_config = HashMap::new();
+        self.path = base_dir;
+        self.name = format!("catalog viewer [ {} ]", selected);
+    }
+
+    /// filter the packages list with the mirror packages filter and the search query being typed
+    // keeps the currently selected package if it still matches
+    pub fn apply_filter(&mut self) {
+        let current_pkg = self
+            .packages
+            .state
+            .selected()
+            .and_then(|id| self.packages.items.get(id).cloned());
+        let mirrored = match self.show_all {
+            true => self.all_packages.clone(),
+            false => filter_packages(&self.all_packages, &self.filters),
+        };
+        // the list is only filtered while typing, afterwards n/N jump between the matches
+        let packages = match self.search_mode {
+            true => filter_items(&mirrored, &self.search),
+            false => mirrored,
+        };
+        let pos = current_pkg
+            .and_then(|pkg| packages.iter().position(|p| p == &pkg))
+            .unwrap_or(0);
+
+        self.packages = StatefulList::with_items(packages);
+        self.packages.state.select(Some(pos));
+        // force a reload of the channels for the selected package
+        self.last_update = usize::MAX;
+    }
+
+    /// handle a key of the search mode ('/')
+    // the list is filtered while typing, enter shows the full list again with the matches
+    // highlighted (n/N jump between them) and esc clears the search
+    pub fn search_key(&mut self, code: KeyCode) {
+        match code {
+            KeyCode::Esc => {
+                self.search.clear();
+                self.search_mode = false;
+                self.apply_filter();
+            }
+            KeyCode::Enter => {
+                self.search_mode = false;
+                self.apply_filter();
+            }
+            KeyCode::Backspace => {
+                self.search.pop();
+                self.apply_filter();
+            }
+            KeyCode::Down => self.packages.next(),
+            KeyCode::Up => self.packages.previous(),
+            KeyCode::Char(c) => {
+                self.search.push(c);
+                self.apply_filter();
+            }
+            _ => {}
+        }
+    }
+
+    /// select the next (or previous) package that matches the search (wraps around)
+    pub fn search_jump(&mut self, forward: bool) {
+        if self.search.is_empty() {
+            self.message = "press / to search the packages".to_string();
+            return;
+        }
+        let len = self.packages.items.len();
+        let current = self.packages.state.selected().unwrap_or(0);
+        let pos = (1..=len)
+            .map(|step| match forward {
+                true => (current + step) % len,
+                false => (current + len - step) % len,
+            })
+            .find(|pos| fuzzy_match(&self.packages.items[*pos], &self.search).is_some());
+        match pos {
+            Some(pos) => self.packages.state.select(Some(pos)),
+            None => self.message = format!("no package matches /{}", self.search),
+        }
+    }
+
+    /// get the mirror packages filter for the package (None when showing the full catalog)
+    pub fn active_filter(&self, pkg: &str) -> Option<&PackageFilter> {
+        match self.show_all {
+            true => None,
+            false => find_filter(&self.filters, pkg),
+        }
+    }
+
+    /// get the selected channel (or the channel the selected bundle is listed under)
+    pub fn selected_channel(&self) -> Option<String> {
+        let id = self.channels.state.selected()?;
+        self.channels
+            .items
+            .get(..=id)?
+            .iter()
+            .rev()
+            .find_map(|c| c.strip_suffix("=olm.channel"))
+            .map(|c| c.to_string())
+    }
+
+    /// get the selected bundle and the channel it is listed under
+    pub fn selected_bundle(&self) -> Option<(String, String)> {
+        let id = self.channels.state.selected()?;
+        let name = self.channels.items.get(id)?;
+        if name.contains("=olm.channel") {
+            return None;
+        }
+        Some((name.trim().to_string(), self.selected_channel()?))
+    }
+
+    /// open the upgrade graph pane for the selected channel
+    // the selected bundle (if any) is preselected in the graph
+    pub fn open_graph(&mut self) {
+        let channel = match self.selected_channel() {
+            Some(channel) => channel,
+            None => return,
+        };
+        let entries = match self
+            .declarative_config
+            .get(&format!("{}=olm.channel", channel))
+        {
+            Some(dc) => dc.entries.clone().unwrap_or_default(),
+            None => return,
+        };
+        let default_channel = get_default_channel(&self.declarative_config);
+        let graph = get_channel_graph(
+            &self.declarative_config,
+            &channel,
+            &entries,
+            default_channel.as_deref() == Some(channel.as_str()),
+        );
+        let lines = graph_lines(&graph);
+        let selected = self.selected_bundle().map(|(bundle, _)| bundle);
+        let pos = lines
+            .iter()
+            .position(|l| l.node.is_some() && l.node == selected)
+            .unwrap_or(0);
+        self.graph = StatefulList::with_items(lines);
+        self.graph.state.select(Some(pos));
+        self.graph_channel = channel;
+        self.show_graph = true;
+    }
+
+    /// move the graph selection to the next or previous node
+    pub fn graph_select(&mut self, forward: bool) {
+        let current = self.graph.state.selected().unwrap_or(0);
+        if let Some(pos) = next_node(&self.graph.items, current, forward) {
+            self.graph.state.select(Some(pos));
+        }
+    }
+
+    /// scroll the graph by a page and select the nearest node
+    pub fn graph_page(&mut self, forward: bool) {
+        let len = self.graph.items.len();
+        if len == 0 {
+            return;
+        }
+        let current = self.graph.state.selected().unwrap_or(0);
+        let pos = match forward {
+            true => (current + 10).min(len - 1),
+            false => current.saturating_sub(10),
+        };
+        let pos = match self.graph.items[pos].node.is_some() {
+            true => Some(pos),
+            false => next_node(&self.graph.items, pos, !forward),
+        };
+        self.graph.state.select(pos);
+    }
+
+    /// close the graph pane and jump to the details of the selected node
+    pub fn graph_jump(&mut self) {
+        let bundle = match self
+            .graph
+            .state
+            .selected()
+            .and_then(|id| self.graph.items.get(id))
+            .and_then(|l| l.node.clone())
+        {
+            Some(bundle) => bundle,
+            None => return,
+        };
+        let header = format!("{}=olm.channel", self.graph_channel);
+        if let Some(start) = self.channels.items.iter().position(|c| c == &header) {
+            let pos = self.channels.items[start + 1..]
+                .iter()
+                .take_while(|c| !c.contains("=olm.channel"))
+                .position(|c| c.trim() == bundle);
+            if let Some(pos) = pos {
+                self.channels.state.select(Some(start + 1 + pos));
+            } else {
+                self.message = format!("{} is excluded by the mirror filter", bundle);
+            }
+        }
+        self.show_graph = false;
+    }
+
+    /// export the upgrade graph of the selected channel as dot and mermaid
+    // the files are written to <export_dir>/<catalog>/ as <package>-<channel>.dot and .mmd
+    pub fn export_graph(&self) -> Result<String, MirrorError> {
+        if self.export_dir.is_empty() {
+            return Err(MirrorError::new("no export directory set"));
+        }
+        let channel = self
+            .selected_channel()
+            .ok_or(MirrorError::new("no channel selected"))?;
+        let package = self
+            .packages
+            .state
+            .selected()
+            .and_then(|id| self.packages.items.get(id))
+            .ok_or(MirrorError::new("no package selected"))?;
+        let graphs = get_package_graphs(package, Some(&channel), &self.declarative_config)?;
+        let dir = Path::new(&self.export_dir).join(self.current_catalog());
+        fs::create_dir_all(&dir)
+            .map_err(|e| MirrorError::new(&format!("creating directory {:?} {}", dir, e)))?;
+        let dir = fs::canonicalize(&dir).unwrap_or(dir);
+        let mut files: Vec<String> = vec![];
+        for format in [GraphFormat::Dot, GraphFormat::Mermaid] {
+            let file = dir.join(format!("{}-{}.{}", package, channel, format.extension()));
+            fs::write(&file, format_graph(package, &graphs, &format) + "\n")
+                .map_err(|e| MirrorError::new(&format!("writing graph {:?} {}", file, e)))?;
+            files.push(file.to_string_lossy().to_string());
+        }
+        Ok(format!("exported {}", files.join(", ")))
+    }
+
+    /// format the upgrade path from the selected bundle to the head of its channel
+    pub fn upgrade_path(&self) -> Option<(String, String)> {
+        let (bundle, channel) = self.selected_bundle()?;
+        let entries = self
+            .declarative_config
+            .get(&format!("{}=olm.channel", channel))?
+            .entries
+            .clone()
+            .unwrap_or_default();
+        let head = get_channel_head(&self.declarative_config, &entries)?;
+        let title = format!("upgrade path [{} -> {}] ", bundle, head);
+        let text =
+            match get_upgrade_path(&self.declarative_config, &channel, &entries, &bundle, &head) {
+                Ok(steps) if steps.is_empty() => "\n  already at the channel head".to_string(),
+                Ok(steps) => steps
+                    .iter()
+                    .map(|s| format!("\n  {} ({})", s.to, s.via))
+                    .collect::<String>(),
+                Err(err) => format!("\n  {}", err),
+            };
+        Some((title, text))
+    }
+
+    /// load every package of the current catalog (kept until the catalog is switched)
+    // returns false (and sets the message) when the catalog can't be loaded
+    pub fn load_catalog_index(&mut self) -> bool {
+        if self.catalog_index.as_ref().map(|(path, _)| path) == Some(&self.path) {
+            return true;
+        }
+        match CatalogIndex::load(&self.path) {
+            Ok(index) => {
+                self.gvk_index = GvkIndex::new(&index);
+                self.reverse_index = ReverseIndex::new(&index, &self.gvk_index);
+                self.catalog_index = Some((self.path.clone(), index));
+                true
+            }
+            Err(err) => {
+                self.message = format!("unable to load catalog {}", err);
+                false
+            }
+        }
+    }
+
+    /// list the gvks that match the provides query (fuzzy) with the bundles that provide them
+    pub fn update_provides(&mut self) {
+        let mut items: Vec<(String, Option<String>)> = vec![];
+        if !self.provides_query.is_empty() {
+            for (gvk, providers) in self.gvk_index.providers.iter() {
+                if fuzzy_match(&gvk.to_string(), &self.provides_query).is_none() {
+                    continue;
+                }
+                items.push((gvk.to_string(), None));
+                for p in providers.iter() {
+                    items.push((
+                        format!(
+                            "  {}  {}{}",
+                            p.package,
+                            p.bundle,
+                            if p.default_head { " *" } else { "" }
+                        ),
+                        Some(p.package.clone()),
+                    ));
+                }
+            }
+        }
+        self.provides = StatefulList::with_items(items);
+        // preselect the first provider
+        let first = self.provides.items.iter().position(|(_, p)| p.is_some());
+        self.provides.state.select(first);
+    }
+
+    /// move to the next (or previous) provider in the provides list (gvk headers are skipped)
+    pub fn provides_select(&mut self, forward: bool) {
+        let len = self.provides.items.len();
+        if len == 0 {
+            return;
+        }
+        let mut pos = self.provides.state.selected().unwrap_or(0);
+        for _ in 0..len {
+            pos = match forward {
+                true => (pos + 1) % len,
+                false => (pos + len - 1) % len,
+            };
+            if self.provides.items[pos].1.is_some() {
+                self.provides.state.select(Some(pos));
+                return;
+            }
+        }
+    }
+
+    /// select the package of the selected provider in the packages list
+    // the search and mirror filter are cleared when they hide the package
+    pub fn provides_jump(&mut self) {
+        let pkg = match self
+            .provides
+            .state
+            .selected()
+            .and_then(|id| self.provides.items.get(id))
+            .and_then(|(_, p)| p.clone())
+        {
+            Some(pkg) => pkg,
+            None => return,
+        };
+        if !self.packages.items.contains(&pkg) {
+            self.search.clear();
+            self.show_all = true;
+            self.apply_filter();
+        }
+        if let Some(pos) = self.packages.items.iter().position(|p| p == &pkg) {
+            self.packages.state.select(Some(pos));
+            self.last_update = usize::MAX;
+        }
+        self.provides_mode = false;
+    }
+
+    /// resolve the transitive dependencies of the selected bundle against the whole catalog
+    pub fn resolve_selected(&mut self) -> Option<(String, Vec<String>)> {
+        let (bundle, _) = self.selected_bundle()?;
+        let pkg = self
+            .packages
+            .state
+            .selected()
+            .and_then(|id| self.packages.items.get(id))?
+            .clone();
+        if !self.load_catalog_index() {
+            return None;
+        }
+        let (_, index) = self.catalog_index.as_ref()?;
+        let title = format!("dependencies [{}] ", bundle);
+        match resolve_dependencies(index, &pkg, &bundle) {
+            Ok(tree) => {
+                let unsatisfied = tree.unsatisfied().len();
+                let title = match unsatisfied {
+                    0 => title,
+                    n => format!("dependencies [{}] ({} unsatisfiable) ", bundle, n),
+                };
+                Some((title, dependency_lines(&tree)))
+            }
+            Err(err) => Some((title, vec![err.to_string()])),
+        }
+    }
+
+    /// list the bundles of other packages that depend on the selected package
+    pub fn package_dependents(&mut self) -> Option<(String, Vec<String>)> {
+        let pkg = self
+            .packages
+            .state
+            .selected()
+            .and_then(|id| self.packages.items.get(id))?
+            .clone();
+        if !self.load_catalog_index() {
+            return None;
+        }
+        let dependents = self.reverse_index.get_dependents(&pkg);
+        let breaks = dependents.iter().filter(|d| d.breaks).count();
+        let title = format!("dependents [{}] ({} break without it) ", pkg, breaks);
+        let text = format_dependents(&dependents, &OutputFormat::Table)
+            .unwrap_or_else(|err| err.to_string());
+        Some((title, text.lines().map(|l| l.to_string()).collect()))
+    }
+
+    /// get the marked packages of the current catalog
+    pub fn current_selection(&self) -> Vec<PackageFilter> {
+        self.selections
+            .get(&self.current_catalog())
+            .cloned()
+            .unwrap_or_default()
+    }
+
+    /// mark (or unmark) the selected package, channel (max false) or bundle version
+    // bundles set the min version ('[') or the max version (']') of their channel
+    pub fn mark(&mut self, target: char) {
+        let pkg = match self
+            .packages
+            .state
+            .selected()
+            .and_then(|id| self.packages.items.get(id))
+        {
+            Some(pkg) => pkg.clone(),
+            None => return,
+        };
+        let mut selection = self.current_selection();
+        match target {
+            'm' => {
+                toggle_package(&mut selection, &pkg);
+            }
+            'M' => {
+                if let Some(channel) = self.selected_channel() {
+                    toggle_channel(&mut selection, &pkg, &channel);
+                }
+            }
+            _ => {
+                let (bundle, channel) = match self.selected_bundle() {
+                    Some(selected) => selected,
+                    None => {
+                        self.message = "select a bundle to set the min/max version".to_string();
+                        return;
+                    }
+                };
+                match get_bundle_version(&self.declarative_config, &bundle) {
+                    Some(version) => toggle_channel_bound(
+                        &mut selection,
+                        &pkg,
+                        &channel,
+                        &version.to_string(),
+                        target == ']',
+                    ),
+                    None => {
+                        self.message = format!("unable to get the version of {}", bundle);
+                        return;
+                    }
+                }
+            }
         }
+        self.selections.insert(self.current_catalog(), selection);
+    }
+
+    /// write the marked packages of all catalogs as an ImageSetConfiguration
+    pub fn write_selection(&self, file: &str) -> Result<String, MirrorError> {
+        let mut catalogs = self
+            .selections
+            .iter()
+            .filter(|(_, v)| !v.is_empty())
+            .collect::<Vec<_>>();
+        if catalogs.is_empty() {
+            return Err(MirrorError::new("no packages marked (use m, M, [ or ])"));
+        }
+        catalogs.sort_by(|a, b| a.0.cmp(b.0));
+        let selections = catalogs
+            .iter()
+            .map(|(k, v)| {
+                // snapshots are keyed as catalog@snapshot
+                let key = k.split('@').next().unwrap_or(k);
+                let image = self
+                    .catalog_images
+                    .get(key)
+                    .cloned()
+                    .unwrap_or(key.to_string());
+                (image, v.to_vec())
+            })
+            .collect::<Vec<(String, Vec<PackageFilter>)>>();
+        let config = format_mirror_config(&ConfigKind::Imageset, &selections)?;
+        fs::write(file, config)
+            .map_err(|e| MirrorError::new(&format!("writing config {} {}", file, e)))?;
+        Ok(format!("wrote {}", file))
+    }
+
+    /// name of the catalog currently being viewed
+    pub fn current_catalog(&self) -> String {
+        self.catalog_map
+            .iter()
+            .find(|(_, v)| **v == self.path)
+            .map(|(k, _)| k.clone())
+            .unwrap_or_default()
     }
 }
 
+/// keys of the main view shown in the help popup ('?')
+const KEY_HELP: &[(&str, &str)] = &[
+    ("▲ ▼", "change package"),
+    ("◄ ►", "change channel/bundle"),
+    ("p", "toggle dependency popup"),
+    ("r", "toggle dependents popup"),
+    ("u", "toggle upgrade path"),
+    ("g", "view channel graph"),
+    ("e", "export channel graph"),
+    ("m M", "mark package/channel"),
+    ("[ ]", "set min/max bundle"),
+    ("w", "write imageset-config.yaml"),
+    ("c", "switch catalog"),
+    ("/", "search packages"),
+    ("n N", "next/previous match"),
+    ("P", "find the providers of a gvk"),
+    ("f", "toggle mirror filter"),
+    ("?", "show this help"),
+    ("q", "quit"),
+];
+
 /// run the app (event loop)
 pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Result<()> {
     loop {
@@ -102,26 +719,156 @@ pub fn run_app<B: Backend>(terminal: &mut Terminal<B>, app: &mut App) -> io::Res
         if let Event::Key(key) = event::read()? {
             if key.kind == KeyEventKind::Press {
                 use KeyCode::*;
+                if app.show_help {
+                    match key.code {
+                        Char('q') => return Ok(()),
+                        _ => app.show_help = false,
+                    }
+                    continue;
+                }
+                if app.show_catalogs {
+                    match key.code {
+                        Char('q') => return Ok(()),
+                        Char('c') | Esc => app.show_catalogs = false,
+                        Down => app.catalogs.next(),
+                        Up => app.catalogs.previous(),
+                        Enter => {
+                            app.switch_catalog();
+                            app.show_catalogs = false;
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                if app.show_graph {
+                    match key.code {
+                        Char('q') => return Ok(()),
+                        Char('g') | Esc => app.show_graph = false,
+                        Down => app.graph_select(true),
+                        Up => app.graph_select(false),
+                        PageDown => app.graph_page(true),
+                        PageUp => app.graph_page(false),
+                        Enter => app.graph_jump(),
+                        _ => {}
+                    }
+                    continue;
+                }
+                if app.provides_mode {
+                    match key.code {
+                        Esc => app.provides_mode = false,
+                        Enter => app.provides_jump(),
+                        Down => app.provides_select(true),
+                        Up => app.provides_select(false),
+                        Backspace => {
+                            app.provides_query.pop();
+                            app.update_provides();
+                        }
+                        Char(c) => {
+                            app.provides_query.push(c);
+                            app.update_provides();
+                        }
+                        _ => {}
+                    }
+                    continue;
+                }
+                if app.search_mode {
+                    app.search_key(key.code);
+                    continue;
+                }
+                app.message.clear();
                 match key.code {
                     Char('q') | Esc => return Ok(()),
                     Down => {
                         app.packages.next();
                         app.show_popup = false;
+                        app.show_upgrade = false;
                     }
                     Up => {
                         app.packages.previous();
                         app.show_popup = false;
+                        app.show_upgrade = false;
                     }
                     Left => {
                         app.channels.previous();
                         app.show_popup = false;
+                        app.show_upgrade = false;
                     }
                     Right => {
                         app.channels.next();
                         app.show_popup = false;
+                        app.show_upgrade = false;
                     }
                     Char('p') => {
+                        app.show_upgrade = false;
                         app.show_popup = !app.show_popup;
+                        if app.show_popup {
+                            app.dependencies = app.resolve_selected();
+                        }
+                    }
+                    Char('r') => {
+                        app.show_upgrade = false;
+                        app.show_popup = !app.show_popup;
+                        if app.show_popup {
+                            app.dependencies = app.package_dependents();
+                        }
+                    }
+                    Char('u') => {
+                        app.show_popup = false;
+                        app.show_upgrade = !app.show_upgrade;
+                    }
+                    Char('g') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        app.open_graph();
+                    }
+                    Char(c @ ('m' | 'M' | '[' | ']')) => app.mark(c),
+                    Char('w') => {
+                        app.message = match app.write_selection("imageset-config.yaml") {
+                            Ok(msg) => msg,
+                            Err(err) => format!("write failed {}", err),
+                        };
+                    }
+                    Char('e') => {
+                        app.message = match app.export_graph() {
+                            Ok(msg) => msg,
+                            Err(err) => format!("export failed {}", err),
+                        };
+                    }
+                    Char('c') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        app.show_catalogs = true;
+                    }
+                    Char('/') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        app.search.clear();
+                        app.search_mode = true;
+                        app.apply_filter();
+                    }
+                    Char('P') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        if app.load_catalog_index() {
+                            app.provides_mode = true;
+                            app.update_provides();
+                        }
+                    }
+                    Char('?') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        app.show_help = true;
+                    }
+                    Char('f') => {
+                        app.show_popup = false;
+                        app.show_upgrade = false;
+                        app.show_all = !app.show_all;
+                        app.apply_filter();
+                    }
+                    Char(c @ ('n' | 'N')) => {
+                        app.search_jump(c == 'n');
+                        app.show_popup = false;
+                        app.show_upgrade = false;
                     }
                     _ => {}
                 }
@@ -147,7 +894,11 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
         )
         .split(size);
 
-    let title = Paragraph::new(app.name.as_str())
+    let title = match app.message.is_empty() {
+        true => app.name.clone(),
+        false => format!("{} ({})", app.name, app.message),
+    };
+    let title = Paragraph::new(title)
         .style(Style::default().fg(Color::Yellow))
         .alignment(Alignment::Center)
         .block(
@@ -174,12 +925,45 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
     let (left, center, right) = render_complex_view(app);
     frame.render_stateful_widget(left, body[0], &mut app.packages.state.clone());
     frame.render_stateful_widget(center, body[1], &mut app.channels.state.clone());
-    frame.render_widget(right, body[2]);
+    if app.show_graph {
+        let items = app
+            .graph
+            .items
+            .iter()
+            .map(|l| {
+                ListItem::new(Line::from(vec![Span::styled(
+                    l.text.clone(),
+                    graph_line_style(&l.kind),
+                )]))
+            })
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .borders(Borders::ALL)
+                    .style(Style::default().fg(Color::White))
+                    .title(format!(
+                        "upgrade graph [{}] [ │ replaces ┆ skips ] [ ▲ ▼ select, pgup/pgdn scroll, enter details, g close ]",
+                        app.graph_channel
+                    ))
+                    .border_type(BorderType::Plain),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(" ");
+        frame.render_stateful_widget(list, body[2], &mut app.graph.state);
+    } else {
+        frame.render_widget(right, body[2]);
+    }
 
     let version = env!["CARGO_PKG_VERSION"];
     let name = env!["CARGO_PKG_NAME"];
     let title = format!(
-        "{} {} 2025 [ use ▲ ▼  to change package,  ◄  ► to change channel/bundle, p to toggle dependency popup, q to quit ]",
+        "{} {} 2025 [ use ▲ ▼  to change package,  ◄  ► to change channel/bundle, / to search, c to switch catalog, ? for help, q to quit ]",
         name, version
     );
 
@@ -197,49 +981,187 @@ pub fn render_ui(frame: &mut Frame, app: &mut App) {
 
     // prepare popup rendering
     if app.show_popup {
-        let id = app.channels.state.selected().unwrap();
-        let selected_name = app.channels.items[id].to_string();
-        if !selected_name.contains("olm.channel") {
-            let mut list_deps = " ".to_string();
-            let dc_res = app
-                .declarative_config
-                .get(&format!("{}=olm.bundle", selected_name.trim()));
-            if dc_res.is_some() {
-                let dc = dc_res.unwrap();
-                if dc.properties.as_ref().is_some() {
-                    for item in dc.properties.as_ref().unwrap().iter() {
-                        if item.type_prop == "olm.package.required" {
-                            if item.value.package_name.is_some() {
-                                let pkg_name = item.value.package_name.as_ref().unwrap();
-                                list_deps = format!("\n  {}{}", pkg_name, list_deps);
-                            }
-                        }
-                    }
-                }
-            }
-            let paragraph = Paragraph::new(list_deps)
+        if let Some((title, lines)) = app.dependencies.as_ref() {
+            let items = lines
+                .iter()
+                .map(|l| {
+                    // unsatisfiable requirements and dependents that break without the package
+                    let style = match l.contains('✗') || l.ends_with("  yes") {
+                        true => Style::default().fg(Color::LightRed),
+                        false => Style::default().fg(Color::White),
+                    };
+                    ListItem::new(Line::from(vec![Span::styled(format!(" {}", l), style)]))
+                })
+                .collect::<Vec<ListItem>>();
+            let list = List::new(items).block(
+                Block::default()
+                    .style(Style::default().fg(Color::White))
+                    .borders(Borders::ALL)
+                    .title(title.clone())
+                    .border_type(BorderType::Plain),
+            );
+            let area = popup_area(size, 60, 40);
+            frame.render_widget(Clear, area);
+            frame.render_widget(list, area);
+        }
+    }
+
+    // prepare upgrade path popup rendering
+    if app.show_upgrade {
+        if let Some((title, text)) = app.upgrade_path() {
+            let paragraph = Paragraph::new(text)
                 .style(Style::default().fg(Color::White))
                 .alignment(Alignment::Left)
                 .block(
                     Block::default()
                         .style(Style::default().fg(Color::White))
                         .borders(Borders::ALL)
-                        .title(format!("dependencies [{}] ", selected_name.trim()))
+                        .title(title)
                         .border_type(BorderType::Plain),
                 );
-            let area = popup_area(size, 30, 32);
+            let area = popup_area(size, 40, 32);
             frame.render_widget(Clear, area);
             frame.render_widget(paragraph, area);
         }
     }
+
+    // prepare provides search rendering
+    if app.provides_mode {
+        let items = match app.provides.items.is_empty() {
+            true => vec![ListItem::new(Line::from(vec![Span::styled(
+                match app.provides_query.is_empty() {
+                    true => " type a group/version/kind (fuzzy)",
+                    false => " no package in the catalog provides a matching gvk",
+                },
+                Style::default().fg(Color::DarkGray),
+            )]))],
+            false => app
+                .provides
+                .items
+                .iter()
+                .map(|(text, pkg)| {
+                    let style = match pkg {
+                        Some(_) => Style::default().fg(Color::White),
+                        None => Style::default()
+                            .fg(Color::LightBlue)
+                            .add_modifier(Modifier::BOLD),
+                    };
+                    ListItem::new(Line::from(vec![Span::styled(format!(" {}", text), style)]))
+                })
+                .collect::<Vec<ListItem>>(),
+        };
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .style(Style::default().fg(Color::White))
+                    .borders(Borders::ALL)
+                    .title(format!(
+                        "provides [{}_] [ ▲ ▼ to select, enter to view the package, esc to close, * is the default channel head ]",
+                        app.provides_query
+                    ))
+                    .border_type(BorderType::Plain),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(" ");
+        let area = popup_area(size, 70, 60);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut app.provides.state.clone());
+    }
+
+    // prepare key help rendering
+    if app.show_help {
+        let items = KEY_HELP
+            .iter()
+            .map(|(key, help)| {
+                ListItem::new(Line::from(vec![
+                    Span::styled(
+                        format!(" {:<8}", key),
+                        Style::default()
+                            .fg(Color::LightBlue)
+                            .add_modifier(Modifier::BOLD),
+                    ),
+                    Span::styled(help.to_string(), Style::default().fg(Color::White)),
+                ]))
+            })
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items).block(
+            Block::default()
+                .style(Style::default().fg(Color::White))
+                .borders(Borders::ALL)
+                .title("keys [ any key to close ]")
+                .border_type(BorderType::Plain),
+        );
+        let area = popup_area(size, 50, 60);
+        frame.render_widget(Clear, area);
+        frame.render_widget(list, area);
+    }
+
+    // prepare catalog picker rendering
+    if app.show_catalogs {
+        let items = app
+            .catalogs
+            .items
+            .iter()
+            .map(|c| {
+                ListItem::new(Line::from(vec![Span::styled(
+                    c.to_string(),
+                    Style::default(),
+                )]))
+            })
+            .collect::<Vec<ListItem>>();
+        let list = List::new(items)
+            .block(
+                Block::default()
+                    .style(Style::default().fg(Color::White))
+                    .borders(Borders::ALL)
+                    .title("catalogs [ ▲ ▼ to select, enter to view, c to close ]")
+                    .border_type(BorderType::Plain),
+            )
+            .highlight_style(
+                Style::default()
+                    .bg(Color::Yellow)
+                    .fg(Color::Black)
+                    .add_modifier(Modifier::BOLD),
+            )
+            .highlight_symbol(" ");
+        let area = popup_area(size, 40, 40);
+        frame.render_widget(Clear, area);
+        frame.render_stateful_widget(list, area, &mut app.catalogs.state.clone());
+    }
 }
 
 /// render the complex view with packages, channels and bundles
 fn render_complex_view<'a>(app: &mut App) -> (List<'a>, List<'a>, Table<'a>) {
+    let pkg_title = if app.search_mode {
+        format!(
+            "packages [ /{}_ ] ({}/{})",
+            app.search,
+            app.packages.items.len(),
+            app.all_packages.len()
+        )
+    } else if !app.search.is_empty() {
+        format!(
+            "packages [ /{} ] ({} matches, n/N to jump)",
+            app.search,
+            filter_items(&app.packages.items, &app.search).len()
+        )
+    } else {
+        "packages".to_string()
+    };
+    let pkg_title = if !app.filters.is_empty() && !app.show_all {
+        format!("{} [mirror filter]", pkg_title)
+    } else {
+        pkg_title
+    };
     let pkg = Block::default()
         .borders(Borders::ALL)
         .style(Style::default().fg(Color::White))
-        .title("packages")
+        .title(pkg_title)
         .border_type(BorderType::Plain);
 
     let ch_block = Block::default()
@@ -248,28 +1170,48 @@ fn render_complex_view<'a>(app: &mut App) -> (List<'a>, List<'a>, Table<'a>) {
         .title("channels&bundles")
         .border_type(BorderType::Plain);
 
+    let selection = app.current_selection();
+    let marked = Span::styled(" ✓", Style::default().fg(Color::LightGreen));
     let mut items: Vec<_> = vec![];
     for x in app.packages.items.iter() {
-        items.push(ListItem::new(Line::from(vec![Span::styled(
-            x.to_string(),
-            Style::default(),
-        )])));
+        let mut line = highlight_line(x, &app.search);
+        if find_filter(&selection, x).is_some() {
+            line.spans.push(marked.clone());
+        }
+        items.push(ListItem::new(line));
     }
 
     let selected_id = app.packages.state.selected().unwrap();
-    let pkg_name = app.packages.items[selected_id].to_string();
 
-    if selected_id != app.last_update {
+    if app.packages.items.is_empty() {
+        // nothing matches the search
+        app.channels.items = vec![];
+        app.declarative_config = HashMap::new();
+        app.last_update = usize::MAX;
+    } else if selected_id != app.last_update {
+        let pkg_name = app.packages.items[selected_id].to_string();
+
         // load the declarative_config for the given package
         let dc_map = DeclarativeConfig::get_declarativeconfig_map(
             app.path.to_string() + &pkg_name + &"/updated-configs/",
         );
 
-        // get the relevant channels
+        // get the relevant channels (honoring the mirror packages filter)
+        let filter = app.active_filter(&pkg_name).cloned();
         let mut ch_map: HashMap<String, Vec<ChannelEntry>> = HashMap::new();
         for (k, v) in dc_map.iter() {
             if k.contains("olm.channel") {
-                ch_map.insert(k.clone(), v.clone().entries.unwrap());
+                let mut entries = v.clone().entries.unwrap();
+                if let Some(f) = filter.as_ref() {
+                    let channel = k.split('=').next().unwrap_or_default();
+                    if !f.includes_channel(channel) {
+                        continue;
+                    }
+                    entries.retain(|e| {
+                        f.includes_version(channel, get_bundle_version(&dc_map, &e.name).as_ref())
+                    });
+                }
+                ch_map.insert(k.clone(), entries);
             }
         }
 
@@ -301,27 +1243,60 @@ fn render_complex_view<'a>(app: &mut App) -> (List<'a>, List<'a>, Table<'a>) {
         }
     }
 
+    // marked channels and min/max bundles of the selected package
+    let pkg_selection = app
+        .packages
+        .state
+        .selected()
+        .and_then(|id| app.packages.items.get(id))
+        .and_then(|pkg| find_filter(&selection, pkg))
+        .cloned();
+    let mut current_channel: Option<ChannelFilter> = None;
     for x in app.channels.items.iter() {
         let name = x.clone().to_string();
         if name.contains("=olm.channel") {
-            if name.contains(&default_channel) {
-                ch_items.push(ListItem::new(Line::from(vec![Span::styled(
+            current_channel = pkg_selection.as_ref().and_then(|f| {
+                let channel = name.split('=').next().unwrap_or_default();
+                f.channels.iter().find(|c| c.name == channel).cloned()
+            });
+            let mut spans = if name.contains(&default_channel) {
+                vec![Span::styled(
                     name,
                     Style::default()
                         .fg(Color::LightBlue)
                         .add_modifier(Modifier::BOLD),
-                )])));
+                )]
             } else {
-                ch_items.push(ListItem::new(Line::from(vec![Span::styled(
-                    name,
-                    Style::default().fg(Color::White),
-                )])));
+                vec![Span::styled(name, Style::default().fg(Color::White))]
+            };
+            if current_channel.is_some() {
+                spans.push(marked.clone());
             }
+            ch_items.push(ListItem::new(Line::from(spans)));
         } else {
-            ch_items.push(ListItem::new(Line::from(vec![Span::styled(
-                name,
+            let mut spans = vec![Span::styled(
+                name.clone(),
                 Style::default().fg(Color::LightYellow),
-            )])));
+            )];
+            if let Some(c) = current_channel.as_ref() {
+                let version = get_bundle_version(&app.declarative_config, name.trim());
+                let is_bound = |bound: &Option<String>| {
+                    version.is_some() && bound.as_deref().and_then(parse_version) == version
+                };
+                if is_bound(&c.min_version) {
+                    spans.push(Span::styled(
+                        " [min",
+                        Style::default().fg(Color::LightGreen),
+                    ));
+                }
+                if is_bound(&c.max_version) {
+                    spans.push(Span::styled(
+                        " max]",
+                        Style::default().fg(Color::LightGreen),
+                    ));
+                }
+            }
+            ch_items.push(ListItem::new(Line::from(spans)));
         }
     }
 
@@ -409,7 +1384,16 @@ fn render_complex_view<'a>(app: &mut App) -> (List<'a>, List<'a>, Table<'a>) {
 
     if cb_name.contains("=olm.channel") {
         let dc = app.declarative_config.get(&cb_name).unwrap();
+        let pkg_name = app.packages.items[app.packages.state.selected().unwrap()].to_string();
+        let filter = app.active_filter(&pkg_name);
+        let channel = cb_name.split('=').next().unwrap_or_default();
         for entry in dc.entries.clone().unwrap().iter() {
+            if let Some(f) = filter {
+                let version = get_bundle_version(&app.declarative_config, &entry.name);
+                if !f.includes_version(channel, version.as_ref()) {
+                    continue;
+                }
+            }
             let e = entry.clone();
             let mut skips = String::from("");
             if e.skips.is_some() {
@@ -477,3 +1461,108 @@ fn popup_area(area: Rect, percent_x: u16, percent_y: u16) -> Rect {
     let [area] = horizontal.areas(area);
     area
 }
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    // app for an empty catalog in <base>/configs/ with the given packages
+    fn test_app(base: &str, packages: &[&str]) -> App {
+        let configs_dir = format!("{}/configs/", base);
+        fs::create_dir_all(&configs_dir).expect("should create render test folder");
+        let mut app = App::new(
+            "test-index:v1.0".to_string(),
+            configs_dir,
+            HashMap::new(),
+            HashMap::new(),
+        );
+        app.all_packages = packages.iter().map(|p| p.to_string()).collect();
+        app.apply_filter();
+        app
+    }
+
+    #[test]
+    fn search_key_pass() {
+        let base = "test-artifacts/search-key-test";
+        let mut app = test_app(
+            base,
+            &[
+                "3scale-operator",
+                "advanced-cluster-management",
+                "albo-operator",
+                "odf-operator",
+                "ocs-operator",
+            ],
+        );
+        // the list is filtered while typing
+        app.search_mode = true;
+        app.search_key(KeyCode::Char('a'));
+        app.search_key(KeyCode::Char('l'));
+        assert_eq!(
+            app.packages.items,
+            vec![
+                "3scale-operator",
+                "advanced-cluster-management",
+                "albo-operator"
+            ]
+        );
+        app.search_key(KeyCode::Down);
+        assert_eq!(app.packages.state.selected(), Some(1));
+        // enter shows the full list and keeps the selected package
+        app.search_key(KeyCode::Enter);
+        assert!(!app.search_mode);
+        assert_eq!(app.packages.items.len(), 5);
+        assert_eq!(app.packages.state.selected(), Some(1));
+        // n/N jump between the matches (wraps around)
+        app.search_jump(true);
+        assert_eq!(app.packages.state.selected(), Some(2));
+        app.search_jump(true);
+        assert_eq!(app.packages.state.selected(), Some(0));
+        app.search_jump(false);
+        assert_eq!(app.packages.state.selected(), Some(2));
+        // esc clears the search
+        app.search_mode = true;
+        app.search_key(KeyCode::Backspace);
+        assert_eq!(app.search, "a");
+        app.search_key(KeyCode::Esc);
+        assert!(app.search.is_empty());
+        assert_eq!(app.packages.items.len(), 5);
+        fs::remove_dir_all(base).expect("should delete search test folder");
+    }
+
+    #[test]
+    fn search_jump_fail() {
+        let base = "test-artifacts/search-jump-test";
+        let mut app = test_app(base, &["albo-operator", "odf-operator"]);
+        app.search_jump(true);
+        assert_eq!(app.message, "press / to search the packages");
+        app.search = "zz".to_string();
+        app.search_jump(true);
+        assert_eq!(app.message, "no package matches /zz");
+        assert_eq!(app.packages.state.selected(), Some(0));
+        fs::remove_dir_all(base).expect("should delete search test folder");
+    }
+
+    #[test]
+    fn export_graph_pass() {
+        let base = "test-artifacts/export-graph-test";
+        let mut app = test_app(base, &["albo-operator"]);
+        assert!(app.export_graph().is_err());
+        app.declarative_config = crate::operator::catalog::declarative_config_from_file(
+            "test-artifacts/declarative-config/albo-operator.json",
+        );
+        app.channels = StatefulList::with_items(vec!["stable-v1=olm.channel".to_string()]);
+        app.channels.state.select(Some(0));
+        app.export_dir = format!("{}/exports", base);
+        let msg = app.export_graph().unwrap();
+        // the written paths are shown in the ui
+        let dir = fs::canonicalize(&app.export_dir).unwrap();
+        for ext in ["dot", "mmd"] {
+            let file = dir.join(format!("albo-operator-stable-v1.{}", ext));
+            assert!(file.exists());
+            assert!(msg.contains(&file.to_string_lossy().to_string()));
+        }
+        fs::remove_dir_all(base).expect("should delete export graph test folder");
+    }
+}
@@ -0,0 +1,107 @@
+use ratatui::prelude::*;
+
+/// fuzzy match the query against the name (case insensitive)
+// returns the char positions in name that matched, or None if there is no match
+pub fn fuzzy_match(name: &str, query: &str) -> Option<Vec<usize>> {
+    let query = query.to_lowercase();
+    let mut positions: Vec<usize> = vec![];
+    let mut chars = name
+        .to_lowercase()
+        .chars()
+        .enumerate()
+        .collect::<Vec<_>>()
+        .into_iter();
+    for q in query.chars() {
+        match chars.find(|(_, c)| *c == q) {
+            Some((pos, _)) => positions.push(pos),
+            None => return None,
+        }
+    }
+    Some(positions)
+}
+
+/// filter the items with the query (keeps the original order)
+pub fn filter_items(items: &[String], query: &str) -> Vec<String> {
+    if query.is_empty() {
+        return items.to_vec();
+    }
+    items
+        .iter()
+        .filter(|i| fuzzy_match(i, query).is_some())
+        .cloned()
+        .collect()
+}
+
+/// build a line with the matched chars highlighted
+pub fn highlight_line<'a>(name: &str, query: &str) -> Line<'a> {
+    let positions = match query.is_empty() {
+        true => None,
+        false => fuzzy_match(name, query),
+    };
+    let positions = match positions {
+        Some(p) => p,
+        None => return Line::from(vec![Span::styled(name.to_string(), Style::default())]),
+    };
+    let spans = name
+        .chars()
+        .enumerate()
+        .map(|(i, c)| {
+            if positions.contains(&i) {
+                Span::styled(
+                    c.to_string(),
+                    Style::default()
+                        .fg(Color::LightGreen)
+                        .add_modifier(Modifier::BOLD | Modifier::UNDERLINED),
+                )
+            } else {
+                Span::styled(c.to_string(), Style::default())
+            }
+        })
+        .collect::<Vec<Span>>();
+    Line::from(spans)
+}
+
+#[cfg(test)]
+mod tests {
+    // this brings everything from parent's scope into this scope
+    use super::*;
+
+    #[test]
+    fn fuzzy_match_pass() {
+        assert_eq!(fuzzy_match("3scale-operator", "3sc"), Some(vec![0, 1, 2]));
+        assert_eq!(
+            fuzzy_match("advanced-cluster-management", "acm"),
+            Some(vec![0, 5, 17])
+        );
+        assert_eq!(fuzzy_match("ODF-operator", "odf"), Some(vec![0, 1, 2]));
+        assert_eq!(fuzzy_match("odf-operator", "fdo"), None);
+    }
+
+    #[test]
+    fn filter_items_pass() {
+        let items = vec![
+            "3scale-operator".to_string(),
+            "advanced-cluster-management".to_string(),
+            "amq-streams".to_string(),
+        ];
+        assert_eq!(filter_items(&items, "").len(), 3);
+        assert_eq!(
+            filter_items(&items, "am"),
+            vec![
+                "advanced-cluster-management".to_string(),
+                "amq-streams".to_string()
+            ]
+        );
+        assert!(filter_items(&items, "xyz").is_empty());
+    }
+
+    #[test]
+    fn highlight_line_pass() {
+        let line = highlight_line("amq-streams", "amq");
+        assert_eq!(line.spans.len(), 11);
+        assert!(line.spans[0].style.add_modifier.contains(Modifier::BOLD));
+        assert!(!line.spans[3].style.add_modifier.contains(Modifier::BOLD));
+        let plain = highlight_line("amq-streams", "");
+        assert_eq!(plain.spans.len(), 1);
+    }
+}